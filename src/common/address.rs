@@ -0,0 +1,209 @@
+use crate::errors::address_errors::AddressError;
+use bech32::primitives::decode::{CheckedHrpstring, CheckedHrpstringError};
+use bech32::{Bech32m, Hrp};
+use serde::Deserialize;
+
+/// Version of the address payload written by `Address::encode`.
+pub const ADDRESS_VERSION: u8 = 0;
+
+const KEY_HASH_LENGTH: usize = 20;
+
+/// Network an address belongs to, encoded as the human-readable prefix of the address so
+/// funds cannot be sent across networks by mistake.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    /// Returns the human-readable prefix of the addresses of the network.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "tok",
+            Network::Testnet => "ttok",
+        }
+    }
+
+    /// Returns the network using the given prefix.
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        [Network::Mainnet, Network::Testnet]
+            .into_iter()
+            .find(|network| network.prefix() == prefix)
+    }
+}
+
+/// An address: the RIPEMD160(SHA256) hash of a public key on a network.
+/// Addresses are written in Bech32m: the network prefix, `1`, then the version byte and the
+/// key hash followed by a checksum, which detects any mistyping of up to four characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address {
+    pub network: Network,
+    pub key_hash: [u8; KEY_HASH_LENGTH],
+}
+
+impl Address {
+    /// Encodes the address in its canonical, lowercase form.
+    pub fn encode(&self) -> String {
+        let mut payload = Vec::with_capacity(1 + KEY_HASH_LENGTH);
+        payload.push(ADDRESS_VERSION);
+        payload.extend_from_slice(&self.key_hash);
+        bech32::encode_lower::<Bech32m>(Hrp::parse_unchecked(self.network.prefix()), &payload)
+            .expect("An address is shorter than the Bech32m length limit")
+    }
+
+    /// Parses an address of any known network.
+    /// - Rejects malformed strings, checksum mismatches and unknown prefixes.
+    /// - Rejects uppercase addresses, so every address has exactly one spelling.
+    /// - Rejects unsupported versions and key hashes that are not 20 bytes long.
+    pub fn parse(address: &str) -> Result<Self, AddressError> {
+        let checked = CheckedHrpstring::new::<Bech32m>(address).map_err(|error| match error {
+            CheckedHrpstringError::Checksum(_) => AddressError::InvalidChecksum,
+            error => AddressError::Malformed {
+                message: error_chain(&error),
+            },
+        })?;
+        let prefix = checked.hrp().to_lowercase();
+        let network =
+            Network::from_prefix(&prefix).ok_or(AddressError::UnknownNetwork { prefix })?;
+        let payload: Vec<u8> = checked.byte_iter().collect();
+        let Some((&version, key_hash)) = payload.split_first() else {
+            return Err(AddressError::InvalidLength { length: 0 });
+        };
+        if version != ADDRESS_VERSION {
+            return Err(AddressError::UnsupportedVersion { version });
+        }
+        let key_hash = <[u8; KEY_HASH_LENGTH]>::try_from(key_hash).map_err(|_| {
+            AddressError::InvalidLength {
+                length: key_hash.len(),
+            }
+        })?;
+
+        let parsed = Address { network, key_hash };
+        if parsed.encode() != address {
+            return Err(AddressError::NotCanonical);
+        }
+        Ok(parsed)
+    }
+
+    /// Parses an address, also rejecting addresses of other networks than `network`.
+    pub fn parse_for(address: &str, network: Network) -> Result<Self, AddressError> {
+        let parsed = Address::parse(address)?;
+        if parsed.network != network {
+            return Err(AddressError::WrongNetwork {
+                expected: network.prefix().to_string(),
+                found: parsed.network.prefix().to_string(),
+            });
+        }
+        Ok(parsed)
+    }
+}
+
+/// Joins the messages of an error and its sources, which `bech32` keeps apart.
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        message = format!("{}: {}", message, error);
+        source = error.source();
+    }
+    message
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address() -> Address {
+        Address {
+            network: Network::Mainnet,
+            key_hash: [0x2a; KEY_HASH_LENGTH],
+        }
+    }
+    #[test]
+    fn addresses_round_trip() {
+        let encoded = address().encode();
+
+        assert!(encoded.starts_with("tok1"), "{} lacks its prefix", encoded);
+        assert_eq!(Address::parse(&encoded), Ok(address()));
+        assert_eq!(
+            Address::parse_for(&encoded, Network::Mainnet),
+            Ok(address())
+        );
+        assert_eq!(address().to_string(), encoded);
+    }
+    #[test]
+    fn mistyped_addresses_are_rejected() {
+        // Arrange
+        let encoded = address().encode();
+        let last = encoded.chars().last().unwrap();
+        let replacement = if last == 'q' { 'p' } else { 'q' };
+        let mistyped = format!("{}{}", &encoded[..encoded.len() - 1], replacement);
+
+        // Act & Assert
+        assert_eq!(
+            Address::parse(&mistyped),
+            Err(AddressError::InvalidChecksum),
+            "A single changed character should fail the checksum"
+        );
+        assert_eq!(
+            Address::parse(&encoded.to_uppercase()),
+            Err(AddressError::NotCanonical)
+        );
+        for malformed in ["", "Bob", "tok1", &encoded[1..]] {
+            assert!(
+                Address::parse(malformed).is_err(),
+                "'{}' should be rejected",
+                malformed
+            );
+        }
+    }
+    #[test]
+    fn addresses_of_other_networks_are_rejected() {
+        let testnet = Address {
+            network: Network::Testnet,
+            ..address()
+        }
+        .encode();
+
+        assert!(testnet.starts_with("ttok1"));
+        assert_eq!(
+            Address::parse_for(&testnet, Network::Mainnet),
+            Err(AddressError::WrongNetwork {
+                expected: "tok".to_string(),
+                found: "ttok".to_string()
+            })
+        );
+        let foreign =
+            bech32::encode_lower::<Bech32m>(Hrp::parse_unchecked("bc"), &[0; 21]).unwrap();
+        assert_eq!(
+            Address::parse(&foreign),
+            Err(AddressError::UnknownNetwork {
+                prefix: "bc".to_string()
+            })
+        );
+    }
+    #[test]
+    fn unsupported_payloads_are_rejected() {
+        let encode = |payload: &[u8]| {
+            bech32::encode_lower::<Bech32m>(Hrp::parse_unchecked("tok"), payload).unwrap()
+        };
+
+        assert_eq!(
+            Address::parse(&encode(&[1; 21])),
+            Err(AddressError::UnsupportedVersion { version: 1 })
+        );
+        assert_eq!(
+            Address::parse(&encode(&[0; 33])),
+            Err(AddressError::InvalidLength { length: 32 })
+        );
+    }
+}
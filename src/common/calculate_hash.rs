@@ -1,54 +1,58 @@
-use crate::core::transaction::Transaction;
+use crate::common::encoding::Encoder;
 use sha2::{Digest, Sha256};
 
-/// Calculates the hash of the block based on its properties.
+/// Calculates the hash of the block based on its header properties.
+/// The transactions are committed to through the Merkle root.
+/// The properties are hashed in their canonical encoding, so no two headers share an input.
 pub fn calculate_block_hash(
     index: u32,
     timestamp: &str,
-    transactions: &Vec<Transaction>,
+    merkle_root: &str,
     previous_hash: &str,
+    beneficiary: &str,
+    bits: u32,
     nonce: u64,
 ) -> String {
-    let input = format!(
-        "{}{}{}{}{}",
-        index,
-        timestamp,
-        transactions_string(transactions),
-        previous_hash,
-        nonce
-    );
+    let mut encoder = Encoder::new();
+    encoder.put_u32(index);
+    encoder.put_str(timestamp);
+    encoder.put_str(merkle_root);
+    encoder.put_str(previous_hash);
+    encoder.put_str(beneficiary);
+    encoder.put_u32(bits);
+    encoder.put_u64(nonce);
 
-    let mut hasher = Sha256::new();
-    hasher.update(input);
-    hex::encode(hasher.finalize())
-}
-
-/// Converts the list of transactions into a string for hashing.
-fn transactions_string(transactions: &Vec<Transaction>) -> String {
-    let mut transactions_string: String = String::new();
-    for transaction in transactions {
-        transactions_string.push_str(transaction.stringify().as_str());
-    }
-    transactions_string
+    hex::encode(Sha256::digest(encoder.finish()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::merkle::calculate_merkle_root;
     use crate::core::transaction::Transaction;
     #[test]
     fn deterministic_and_unique_block_hash() {
         let index = 1;
         let timestamp = "2025-01-01T00:00:00Z";
         let transactions = vec![
-            Transaction::new("Alice".to_string(), "Bob".to_string(), 100),
-            Transaction::new("Charlie".to_string(), "Dave".to_string(), 200),
+            Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 0, 0),
+            Transaction::new("Charlie".to_string(), "Dave".to_string(), 200, 0, 0),
         ];
+        let merkle_root = calculate_merkle_root(&transactions);
         let previous_hash = "0000000000000000000000000000000000000000000000000000000000000000";
+        let beneficiary = "Miner";
+        let bits = 0x2000_ffff;
         let nonce = 12345;
 
-        let block_hash =
-            calculate_block_hash(index, timestamp, &transactions, previous_hash, nonce);
+        let block_hash = calculate_block_hash(
+            index,
+            timestamp,
+            &merkle_root,
+            previous_hash,
+            beneficiary,
+            bits,
+            nonce,
+        );
 
         // Check that the hash has the expected length (SHA256 is 64 hex characters)
         assert_eq!(
@@ -58,41 +62,100 @@ mod tests {
         );
 
         // Hash is deterministic (same input should produce the same hash)
-        let block_hash_recalculated =
-            calculate_block_hash(index, timestamp, &transactions, previous_hash, nonce);
+        let block_hash_recalculated = calculate_block_hash(
+            index,
+            timestamp,
+            &merkle_root,
+            previous_hash,
+            beneficiary,
+            bits,
+            nonce,
+        );
         assert_eq!(
             block_hash, block_hash_recalculated,
             "Hash calculation should be deterministic for the same input"
         );
 
         //  A small change in the input results in a different hash
-        let different_hash =
-            calculate_block_hash(index + 1, timestamp, &transactions, previous_hash, nonce);
+        let different_hash = calculate_block_hash(
+            index + 1,
+            timestamp,
+            &merkle_root,
+            previous_hash,
+            beneficiary,
+            bits,
+            nonce,
+        );
         assert_ne!(
             block_hash, different_hash,
             "Hashes should be different for different block indices"
         );
+
+        // The transactions are part of the hashed data through the Merkle root
+        let mut replayed_transactions = transactions.clone();
+        replayed_transactions[0].nonce += 1;
+        let replayed_hash = calculate_block_hash(
+            index,
+            timestamp,
+            &calculate_merkle_root(&replayed_transactions),
+            previous_hash,
+            beneficiary,
+            bits,
+            nonce,
+        );
+        assert_ne!(
+            block_hash, replayed_hash,
+            "Hashes should be different for different transaction nonces"
+        );
+
+        // The beneficiary is part of the hashed data
+        let redirected_hash = calculate_block_hash(
+            index,
+            timestamp,
+            &merkle_root,
+            previous_hash,
+            "Thief",
+            bits,
+            nonce,
+        );
+        assert_ne!(
+            block_hash, redirected_hash,
+            "Hashes should be different for different beneficiaries"
+        );
+
+        let harder_hash = calculate_block_hash(
+            index,
+            timestamp,
+            &merkle_root,
+            previous_hash,
+            beneficiary,
+            bits + 1,
+            nonce,
+        );
+        assert_ne!(
+            block_hash, harder_hash,
+            "Hashes should be different for different targets"
+        );
     }
     #[test]
-    fn transactions_string_concatenation() {
-        // Arrange: Create a list of transactions
-        let transactions = vec![
-            Transaction::new("Alice".to_string(), "Bob".to_string(), 100),
-            Transaction::new("Charlie".to_string(), "Dave".to_string(), 200),
-        ];
+    fn shifted_header_fields_do_not_collide() {
+        // Concatenating these headers field by field yields the same string
+        let first = calculate_block_hash(1, "2025", "1abc", "def", "Miner", 1, 5);
+        let second = calculate_block_hash(12, "025", "1abc", "def", "Miner", 1, 5);
+        let third = calculate_block_hash(1, "20251", "abc", "def", "Miner", 1, 5);
+        let fourth = calculate_block_hash(1, "2025", "1abc", "de", "fMiner", 1, 5);
 
-        // Act: Convert transactions to a string
-        let transactions_str = transactions_string(&transactions);
-
-        // Assert: Check the expected concatenated string
-        let expected_str = format!(
-            "{}{}",
-            transactions[0].stringify(),
-            transactions[1].stringify()
+        assert_ne!(
+            first, second,
+            "Index digits should not shift into the timestamp"
         );
-        assert_eq!(
-            transactions_str, expected_str,
-            "Transaction string should concatenate all transaction string representations"
+        assert_ne!(
+            first, third,
+            "Timestamp should not shift into the Merkle root"
+        );
+        assert_ne!(
+            first, fourth,
+            "Previous hash should not shift into the beneficiary"
         );
     }
 }
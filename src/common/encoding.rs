@@ -0,0 +1,207 @@
+use crate::errors::encoding_errors::DecodeError;
+
+/// Version of the canonical byte encoding, written as the first byte of every encoding.
+pub const ENCODING_VERSION: u8 = 3;
+
+/// Writes values in the canonical byte encoding.
+/// - Integers are written as fixed-width big-endian bytes.
+/// - Byte strings and strings are prefixed with their length as a `u32`.
+/// - Optional values are prefixed with a presence byte (`0` or `1`).
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    /// Creates an encoder that starts with the encoding version.
+    pub fn new() -> Self {
+        Encoder {
+            bytes: vec![ENCODING_VERSION],
+        }
+    }
+
+    pub fn put_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn put_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn put_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn put_bytes(&mut self, value: &[u8]) {
+        self.put_u32(value.len() as u32);
+        self.bytes.extend_from_slice(value);
+    }
+
+    pub fn put_str(&mut self, value: &str) {
+        self.put_bytes(value.as_bytes());
+    }
+
+    /// Writes the presence byte of an optional value; the caller writes the value itself.
+    pub fn put_presence(&mut self, present: bool) {
+        self.put_u8(present as u8);
+    }
+
+    /// Returns the encoded bytes.
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads values written by `Encoder`.
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Creates a decoder and checks the encoding version.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        let mut decoder = Decoder { bytes, position: 0 };
+        let version = decoder.get_u8()?;
+        if version != ENCODING_VERSION {
+            return Err(DecodeError::UnsupportedVersion { version });
+        }
+        Ok(decoder)
+    }
+
+    pub fn get_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn get_u32(&mut self) -> Result<u32, DecodeError> {
+        let bytes: [u8; 4] = self.take(4)?.try_into().unwrap();
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    pub fn get_u64(&mut self) -> Result<u64, DecodeError> {
+        let bytes: [u8; 8] = self.take(8)?.try_into().unwrap();
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    pub fn get_bytes(&mut self) -> Result<&'a [u8], DecodeError> {
+        let length = self.get_u32()? as usize;
+        self.take(length)
+    }
+
+    pub fn get_str(&mut self) -> Result<String, DecodeError> {
+        let bytes = self.get_bytes()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidUtf8)
+    }
+
+    /// Reads the presence byte of an optional value.
+    pub fn get_presence(&mut self) -> Result<bool, DecodeError> {
+        match self.get_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(DecodeError::InvalidPresenceTag { tag }),
+        }
+    }
+
+    /// Ensures all bytes have been read, so every value has exactly one encoding.
+    pub fn finish(self) -> Result<(), DecodeError> {
+        let remaining = self.bytes.len() - self.position;
+        if remaining > 0 {
+            return Err(DecodeError::TrailingBytes { count: remaining });
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn encoded_values_round_trip() {
+        let mut encoder = Encoder::new();
+        encoder.put_u8(7);
+        encoder.put_u32(123_456);
+        encoder.put_u64(u64::MAX);
+        encoder.put_str("Alice");
+        encoder.put_presence(true);
+        encoder.put_bytes(&[1, 2, 3]);
+        encoder.put_presence(false);
+        let bytes = encoder.finish();
+
+        assert_eq!(
+            bytes[0], ENCODING_VERSION,
+            "Version should be written first"
+        );
+
+        let mut decoder = Decoder::new(&bytes).unwrap();
+        assert_eq!(decoder.get_u8(), Ok(7));
+        assert_eq!(decoder.get_u32(), Ok(123_456));
+        assert_eq!(decoder.get_u64(), Ok(u64::MAX));
+        assert_eq!(decoder.get_str(), Ok("Alice".to_string()));
+        assert_eq!(decoder.get_presence(), Ok(true));
+        assert_eq!(decoder.get_bytes(), Ok(&[1u8, 2, 3][..]));
+        assert_eq!(decoder.get_presence(), Ok(false));
+        assert_eq!(decoder.finish(), Ok(()), "All bytes should be consumed");
+    }
+    #[test]
+    fn strings_are_length_prefixed() {
+        let mut first = Encoder::new();
+        first.put_str("AliceB");
+        first.put_str("ob");
+        let mut second = Encoder::new();
+        second.put_str("Alice");
+        second.put_str("Bob");
+
+        assert_ne!(
+            first.finish(),
+            second.finish(),
+            "Moving characters between strings should change the encoding"
+        );
+    }
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert_eq!(
+            Decoder::new(&[]).err(),
+            Some(DecodeError::UnexpectedEnd),
+            "Empty input has no version"
+        );
+        assert_eq!(
+            Decoder::new(&[ENCODING_VERSION + 1]).err(),
+            Some(DecodeError::UnsupportedVersion {
+                version: ENCODING_VERSION + 1
+            }),
+            "Unknown versions should be rejected"
+        );
+
+        // Length prefix pointing past the end of the input
+        let mut decoder = Decoder::new(&[ENCODING_VERSION, 0, 0, 0, 9, b'a']).unwrap();
+        assert_eq!(decoder.get_str(), Err(DecodeError::UnexpectedEnd));
+
+        // Invalid UTF-8 string
+        let mut decoder = Decoder::new(&[ENCODING_VERSION, 0, 0, 0, 1, 0xff]).unwrap();
+        assert_eq!(decoder.get_str(), Err(DecodeError::InvalidUtf8));
+
+        // Presence byte other than 0 or 1
+        let mut decoder = Decoder::new(&[ENCODING_VERSION, 2]).unwrap();
+        assert_eq!(
+            decoder.get_presence(),
+            Err(DecodeError::InvalidPresenceTag { tag: 2 })
+        );
+
+        // Bytes left after decoding
+        let decoder = Decoder::new(&[ENCODING_VERSION, 1, 2]).unwrap();
+        assert_eq!(
+            decoder.finish(),
+            Err(DecodeError::TrailingBytes { count: 2 })
+        );
+    }
+}
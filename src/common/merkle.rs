@@ -0,0 +1,214 @@
+use crate::core::transaction::Transaction;
+use sha2::{Digest, Sha256};
+
+/// Merkle root of a block without transactions.
+pub const EMPTY_MERKLE_ROOT: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Proof that a transaction hash is part of a Merkle tree.
+/// Lets a client check that a transaction is included in a block by knowing only the
+/// block's Merkle root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    pub index: usize,          // Position of the transaction in the block
+    pub siblings: Vec<String>, // Sibling hashes from the leaf level up to the root
+}
+
+impl MerkleProof {
+    /// Verifies that the transaction hash is included in the tree with the given root.
+    /// - The bits of `index` decide on which side each sibling is hashed.
+    pub fn verify(&self, transaction_hash: &str, merkle_root: &str) -> bool {
+        if self.siblings.len() < usize::BITS as usize && self.index >> self.siblings.len() != 0 {
+            return false; // Index does not fit in a tree of this height
+        }
+
+        let mut hash = transaction_hash.to_string();
+        let mut index = self.index;
+        for sibling in &self.siblings {
+            hash = if index.is_multiple_of(2) {
+                hash_pair(&hash, sibling)
+            } else {
+                hash_pair(sibling, &hash)
+            };
+            index /= 2;
+        }
+        hash == merkle_root
+    }
+}
+
+/// Calculates the Merkle root over the hashes of the given transactions.
+pub fn calculate_merkle_root(transactions: &[Transaction]) -> String {
+    merkle_root(&transaction_hashes(transactions))
+}
+
+/// Returns the hashes of the given transactions, which form the leaves of the Merkle tree.
+pub fn transaction_hashes(transactions: &[Transaction]) -> Vec<String> {
+    transactions
+        .iter()
+        .map(|transaction| transaction.hash())
+        .collect()
+}
+
+/// Calculates the Merkle root over the given leaf hashes.
+/// - A level with an odd number of hashes pairs its last hash with itself, so repeating the
+///   last leaves can keep the root (CVE-2012-2459); blocks with duplicate transactions are
+///   rejected for this reason.
+/// - An empty list of leaves results in `EMPTY_MERKLE_ROOT`.
+pub fn merkle_root(leaves: &[String]) -> String {
+    if leaves.is_empty() {
+        return EMPTY_MERKLE_ROOT.to_string();
+    }
+
+    let mut level: Vec<String> = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.remove(0)
+}
+
+/// Builds the inclusion proof for the leaf at the given index.
+/// Returns `None` if the index is out of range.
+pub fn merkle_proof(leaves: &[String], index: usize) -> Option<MerkleProof> {
+    if index >= leaves.len() {
+        return None;
+    }
+
+    let mut siblings: Vec<String> = Vec::new();
+    let mut level: Vec<String> = leaves.to_vec();
+    let mut position = index;
+    while level.len() > 1 {
+        let sibling = if position.is_multiple_of(2) {
+            level.get(position + 1).unwrap_or(&level[position])
+        } else {
+            &level[position - 1]
+        };
+        siblings.push(sibling.clone());
+        level = next_level(&level);
+        position /= 2;
+    }
+
+    Some(MerkleProof { index, siblings })
+}
+
+/// Hashes each pair of the level into the level above it.
+fn next_level(level: &[String]) -> Vec<String> {
+    level
+        .chunks(2)
+        .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+/// Hashes two child hashes into their parent hash.
+fn hash_pair(left: &str, right: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("{:064x}", i)).collect()
+    }
+    #[test]
+    fn merkle_root_of_empty_and_single_leaf() {
+        assert_eq!(
+            merkle_root(&[]),
+            EMPTY_MERKLE_ROOT,
+            "Empty tree should have the empty root"
+        );
+
+        let single = leaves(1);
+        assert_eq!(
+            merkle_root(&single),
+            single[0],
+            "Root of a single leaf should be the leaf itself"
+        );
+    }
+    #[test]
+    fn merkle_root_pairs_and_duplicates_odd_leaves() {
+        let three = leaves(3);
+        let expected = hash_pair(
+            &hash_pair(&three[0], &three[1]),
+            &hash_pair(&three[2], &three[2]),
+        );
+
+        assert_eq!(
+            merkle_root(&three),
+            expected,
+            "Odd levels should pair the last hash with itself"
+        );
+    }
+    #[test]
+    fn merkle_root_changes_with_leaf_order_and_content() {
+        let original = leaves(4);
+        let mut reordered = original.clone();
+        reordered.swap(0, 1);
+        let mut modified = original.clone();
+        modified[3] = format!("{:064x}", 99);
+
+        assert_ne!(
+            merkle_root(&original),
+            merkle_root(&reordered),
+            "Root should depend on the order of the leaves"
+        );
+        assert_ne!(
+            merkle_root(&original),
+            merkle_root(&modified),
+            "Root should depend on the content of the leaves"
+        );
+    }
+    #[test]
+    fn proofs_verify_for_every_leaf() {
+        for count in 1..=9 {
+            let leaves = leaves(count);
+            let root = merkle_root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = merkle_proof(&leaves, index).unwrap();
+                assert!(
+                    proof.verify(leaf, &root),
+                    "Proof for leaf {} of {} should verify",
+                    index,
+                    count
+                );
+            }
+        }
+    }
+    #[test]
+    fn proof_rejects_wrong_leaf_index_or_root() {
+        let leaves = leaves(5);
+        let root = merkle_root(&leaves);
+        let proof = merkle_proof(&leaves, 2).unwrap();
+
+        assert!(
+            !proof.verify(&leaves[3], &root),
+            "Proof should not verify another leaf"
+        );
+        assert!(
+            !proof.verify(&leaves[2], EMPTY_MERKLE_ROOT),
+            "Proof should not verify against another root"
+        );
+
+        let mut moved = proof.clone();
+        moved.index = 3;
+        assert!(
+            !moved.verify(&leaves[2], &root),
+            "Proof should not verify at another position"
+        );
+
+        let mut out_of_range = proof.clone();
+        out_of_range.index = 2 + (1 << proof.siblings.len());
+        assert!(
+            !out_of_range.verify(&leaves[2], &root),
+            "Proof should not verify with an index beyond the tree"
+        );
+    }
+    #[test]
+    fn proof_for_missing_leaf_is_none() {
+        assert_eq!(merkle_proof(&leaves(3), 3), None, "Index 3 is out of range");
+        assert_eq!(merkle_proof(&[], 0), None, "Empty tree has no proofs");
+    }
+}
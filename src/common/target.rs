@@ -0,0 +1,204 @@
+use crate::common::u256::U256;
+
+/// Converts compact "bits" into the 256-bit target they encode.
+/// The bits store the target as `mantissa * 256^(exponent - 3)`:
+/// - The highest byte holds the exponent, the length of the target in bytes.
+/// - The lower three bytes hold the mantissa, whose highest bit is a sign bit.
+///
+/// Returns `None` for negative, zero or overflowing targets, which no hash can meet.
+pub fn compact_to_target(bits: u32) -> Option<U256> {
+    let exponent = bits >> 24;
+    let mantissa = bits & 0x007f_ffff;
+    if bits & 0x0080_0000 != 0 || mantissa == 0 {
+        return None;
+    }
+
+    let target = if exponent <= 3 {
+        U256::from_u64((mantissa >> (8 * (3 - exponent))) as u64)
+    } else {
+        let shift = 8 * (exponent - 3);
+        if u32::BITS - mantissa.leading_zeros() + shift > 256 {
+            return None;
+        }
+        U256::from_u64(mantissa as u64) << shift
+    };
+    (!target.is_zero()).then_some(target)
+}
+
+/// Converts a target into compact "bits", rounding it down to a 23-bit mantissa.
+pub fn target_to_compact(target: U256) -> u32 {
+    let mut size = target.bits().div_ceil(8);
+    let mut mantissa = if size <= 3 {
+        (target.low_u64() << (8 * (3 - size))) as u32
+    } else {
+        (target >> (8 * (size - 3))).low_u64() as u32
+    };
+
+    // Keep the sign bit clear by moving the mantissa into the next byte
+    if mantissa & 0x0080_0000 != 0 {
+        mantissa >>= 8;
+        size += 1;
+    }
+    mantissa | size << 24
+}
+
+/// Returns `true` if the hex-encoded hash, read as a 256-bit number, does not exceed the
+/// target encoded in the bits.
+pub fn hash_meets_target(hash: &str, bits: u32) -> bool {
+    let (Some(target), Ok(bytes)) = (compact_to_target(bits), hex::decode(hash)) else {
+        return false;
+    };
+    let Ok(bytes) = <[u8; 32]>::try_from(bytes) else {
+        return false;
+    };
+    U256::from_be_bytes(bytes) <= target
+}
+
+/// Returns the work of a block with the given bits: the expected number of hashes needed to
+/// meet its target, `2^256 / (target + 1)`.
+/// Invalid bits represent no work.
+pub fn block_work(bits: u32) -> U256 {
+    let Some(target) = compact_to_target(bits) else {
+        return U256::ZERO;
+    };
+    // 2^256 does not fit in 256 bits, but `(2^256 - target - 1) / (target + 1) + 1` is equal
+    match target.checked_add(U256::ONE) {
+        Some(divisor) => (!target)
+            .checked_div(divisor)
+            .unwrap()
+            .saturating_add(U256::ONE),
+        None => U256::ONE,
+    }
+}
+
+/// Scales the target encoded in the bits by the time blocks took, relative to the time they
+/// should have taken, and returns the new bits.
+/// - The actual time is clamped between a quarter and four times the target time, so a
+///   single adjustment never changes the target by more than a factor of four.
+/// - The new target never exceeds the limit, the easiest target allowed.
+pub fn retarget(bits: u32, actual_time: u64, target_time: u64, limit: U256) -> u32 {
+    let Some(target) = compact_to_target(bits) else {
+        return bits;
+    };
+    if target_time == 0 {
+        return bits;
+    }
+
+    let actual_time = actual_time.clamp(target_time / 4, target_time.saturating_mul(4));
+    let new_target = target
+        .mul_div(actual_time, target_time)
+        .unwrap_or(limit)
+        .min(limit)
+        .max(U256::ONE);
+    target_to_compact(new_target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn compact_bits_round_trip() {
+        // Target of the Bitcoin genesis block
+        let target = compact_to_target(0x1d00_ffff).unwrap();
+        assert_eq!(
+            target,
+            U256::from_u64(0xffff) << 208,
+            "Mantissa should be shifted by the exponent"
+        );
+        assert_eq!(target_to_compact(target), 0x1d00_ffff);
+
+        for bits in [0x0312_3456, 0x0412_3456, 0x2000_ffff, 0x2100_ffff] {
+            let target = compact_to_target(bits).unwrap();
+            assert_eq!(target_to_compact(target), bits, "Bits {:#x}", bits);
+        }
+
+        // A mantissa with the sign bit set moves into the next byte
+        assert_eq!(target_to_compact(U256::from_u64(0x80)), 0x0200_8000);
+        assert_eq!(compact_to_target(0x0200_8000), Some(U256::from_u64(0x80)));
+    }
+    #[test]
+    fn targets_are_rounded_down_to_the_mantissa() {
+        let target = U256::from_u64(0x1234_5678) << 104;
+
+        let rounded = compact_to_target(target_to_compact(target)).unwrap();
+
+        assert!(rounded <= target, "Rounding should never ease the target");
+        assert_eq!(rounded, U256::from_u64(0x12_3456) << 112);
+    }
+    #[test]
+    fn invalid_bits_have_no_target() {
+        assert_eq!(compact_to_target(0x0000_0000), None, "Zero target");
+        assert_eq!(compact_to_target(0x1d00_0000), None, "Zero mantissa");
+        assert_eq!(compact_to_target(0x0100_0012), None, "Rounds to zero");
+        assert_eq!(compact_to_target(0x1d80_ffff), None, "Negative target");
+        assert_eq!(compact_to_target(0x2201_0000), None, "Exceeds 256 bits");
+    }
+    #[test]
+    fn hashes_are_compared_numerically_with_the_target() {
+        let bits = 0x2000_ffff; // Target 0x00ffff00…00
+
+        assert!(!hash_meets_target(&format!("00{}", "f".repeat(62)), bits));
+        assert!(hash_meets_target(
+            &format!("00ffff{}", "0".repeat(58)),
+            bits
+        ));
+        assert!(hash_meets_target(
+            &format!("00fffe{}", "f".repeat(58)),
+            bits
+        ));
+        assert!(
+            !hash_meets_target(&format!("01{}", "0".repeat(62)), bits),
+            "Hash above the target should fail"
+        );
+        assert!(!hash_meets_target("00", bits), "Hash must have 32 bytes");
+        assert!(!hash_meets_target("not hex", bits));
+        assert!(!hash_meets_target(&"0".repeat(64), 0), "Invalid bits");
+    }
+    #[test]
+    fn block_work_is_inverse_to_the_target() {
+        assert_eq!(
+            block_work(0x1d00_ffff),
+            U256::from_u64(0x1_0001_0001),
+            "Work of the Bitcoin genesis block"
+        );
+        assert_eq!(block_work(0x2000_ffff), U256::from_u64(256));
+        assert!(
+            block_work(0x1f00_ffff) > block_work(0x2000_ffff),
+            "Lower target means more work"
+        );
+        assert_eq!(block_work(0), U256::ZERO, "Invalid bits have no work");
+    }
+    #[test]
+    fn retarget_scales_and_clamps_the_target() {
+        let limit = compact_to_target(0x2000_ffff).unwrap();
+        let bits = 0x1f00_ffff;
+        let target = compact_to_target(bits).unwrap();
+
+        assert_eq!(retarget(bits, 600, 600, limit), bits, "On target");
+        assert_eq!(
+            compact_to_target(retarget(bits, 300, 600, limit)),
+            Some(target >> 1),
+            "Twice as fast halves the target"
+        );
+        assert_eq!(
+            compact_to_target(retarget(bits, 900, 600, limit)),
+            compact_to_target(target_to_compact(target.mul_div(3, 2).unwrap())),
+            "Adjustments are finer than factors of two"
+        );
+        assert_eq!(
+            compact_to_target(retarget(bits, 0, 600, limit)),
+            Some(target >> 2),
+            "Adjustment is limited to a factor of four"
+        );
+        assert_eq!(
+            compact_to_target(retarget(bits, 60_000, 600, limit)),
+            Some(target << 2),
+            "Adjustment is limited to a factor of four"
+        );
+        assert_eq!(
+            retarget(0x2000_ffff, 60_000, 600, limit),
+            0x2000_ffff,
+            "Target never exceeds the limit"
+        );
+    }
+}
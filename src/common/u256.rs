@@ -0,0 +1,283 @@
+/// Unsigned 256-bit integer used for proof-of-work targets and chain work.
+/// The limbs are stored most significant first, so the derived ordering is numeric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([0, 0, 0, 1]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub fn from_u64(value: u64) -> Self {
+        U256([0, 0, 0, value])
+    }
+
+    /// Interprets 32 bytes as a big-endian number.
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        U256(limbs)
+    }
+
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == U256::ZERO
+    }
+
+    /// Returns the least significant 64 bits.
+    pub fn low_u64(&self) -> u64 {
+        self.0[3]
+    }
+
+    /// Returns the number of bits needed to represent the number.
+    pub fn bits(&self) -> u32 {
+        for (i, limb) in self.0.iter().enumerate() {
+            if *limb != 0 {
+                return (3 - i as u32) * 64 + (64 - limb.leading_zeros());
+            }
+        }
+        0
+    }
+
+    pub fn checked_add(self, other: U256) -> Option<U256> {
+        let mut limbs = [0u64; 4];
+        let mut carry = false;
+        for i in (0..4).rev() {
+            let (sum, overflow) = self.0[i].overflowing_add(other.0[i]);
+            let (sum, carried) = sum.overflowing_add(carry as u64);
+            limbs[i] = sum;
+            carry = overflow || carried;
+        }
+        (!carry).then_some(U256(limbs))
+    }
+
+    pub fn saturating_add(self, other: U256) -> U256 {
+        self.checked_add(other).unwrap_or(U256::MAX)
+    }
+
+    pub fn checked_sub(self, other: U256) -> Option<U256> {
+        if self < other {
+            return None;
+        }
+        let mut limbs = [0u64; 4];
+        let mut borrow = false;
+        for i in (0..4).rev() {
+            let (difference, underflow) = self.0[i].overflowing_sub(other.0[i]);
+            let (difference, borrowed) = difference.overflowing_sub(borrow as u64);
+            limbs[i] = difference;
+            borrow = underflow || borrowed;
+        }
+        Some(U256(limbs))
+    }
+
+    /// Divides by another number, rounding down.
+    /// Returns `None` when dividing by zero.
+    pub fn checked_div(self, divisor: U256) -> Option<U256> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for bit in (0..self.bits()).rev() {
+            remainder = remainder << 1;
+            if self.bit(bit) {
+                remainder.0[3] |= 1;
+            }
+            if remainder >= divisor {
+                remainder = remainder.checked_sub(divisor).unwrap();
+                quotient.0[3 - (bit / 64) as usize] |= 1 << (bit % 64);
+            }
+        }
+        Some(quotient)
+    }
+
+    /// Calculates `self * multiplier / divisor` without losing precision in between.
+    /// Returns `None` when dividing by zero or if the result does not fit in 256 bits.
+    pub fn mul_div(self, multiplier: u64, divisor: u64) -> Option<U256> {
+        if divisor == 0 {
+            return None;
+        }
+
+        // Multiply into five limbs, so the product cannot overflow
+        let mut product = [0u64; 5];
+        let mut carry: u128 = 0;
+        for i in (0..4).rev() {
+            let value = self.0[i] as u128 * multiplier as u128 + carry;
+            product[i + 1] = value as u64;
+            carry = value >> 64;
+        }
+        product[0] = carry as u64;
+
+        // Divide the five limbs, most significant first
+        let mut remainder: u128 = 0;
+        for limb in product.iter_mut() {
+            let value = (remainder << 64) | *limb as u128;
+            *limb = (value / divisor as u128) as u64;
+            remainder = value % divisor as u128;
+        }
+
+        if product[0] != 0 {
+            return None;
+        }
+        Some(U256([product[1], product[2], product[3], product[4]]))
+    }
+
+    fn bit(&self, index: u32) -> bool {
+        self.0[3 - (index / 64) as usize] >> (index % 64) & 1 == 1
+    }
+}
+
+/// Shifts left by the given number of bits; bits shifted out are lost.
+impl std::ops::Shl<u32> for U256 {
+    type Output = U256;
+
+    fn shl(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let source = i + limb_shift;
+            if source < 4 {
+                *limb = self.0[source] << bit_shift;
+                if bit_shift > 0 && source + 1 < 4 {
+                    *limb |= self.0[source + 1] >> (64 - bit_shift);
+                }
+            }
+        }
+        U256(limbs)
+    }
+}
+
+/// Shifts right by the given number of bits.
+impl std::ops::Shr<u32> for U256 {
+    type Output = U256;
+
+    fn shr(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            if i >= limb_shift {
+                let source = i - limb_shift;
+                *limb = self.0[source] >> bit_shift;
+                if bit_shift > 0 && source > 0 {
+                    *limb |= self.0[source - 1] << (64 - bit_shift);
+                }
+            }
+        }
+        U256(limbs)
+    }
+}
+
+/// Returns the bitwise complement, which equals `U256::MAX - self`.
+impl std::ops::Not for U256 {
+    type Output = U256;
+
+    fn not(self) -> U256 {
+        U256(self.0.map(|limb| !limb))
+    }
+}
+
+impl std::fmt::Display for U256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.to_be_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn bytes_round_trip_and_order_numerically() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 1;
+        bytes[31] = 2;
+        let value = U256::from_be_bytes(bytes);
+
+        assert_eq!(value.to_be_bytes(), bytes, "Bytes should round trip");
+        assert_eq!(value.bits(), 249, "Highest bit is bit 248");
+        assert_eq!(value.low_u64(), 2);
+        assert!(
+            value > U256::from_u64(u64::MAX),
+            "High limbs should be more significant"
+        );
+        assert_eq!(U256::ZERO.bits(), 0);
+    }
+    #[test]
+    fn addition_and_subtraction_carry_across_limbs() {
+        let low_max = U256::from_u64(u64::MAX);
+
+        assert_eq!(
+            low_max.checked_add(U256::ONE),
+            Some(U256::ONE << 64),
+            "Addition should carry into the next limb"
+        );
+        assert_eq!(U256::MAX.checked_add(U256::ONE), None);
+        assert_eq!(U256::MAX.saturating_add(U256::ONE), U256::MAX);
+
+        assert_eq!(
+            (U256::ONE << 64).checked_sub(U256::ONE),
+            Some(low_max),
+            "Subtraction should borrow from the next limb"
+        );
+        assert_eq!(U256::ZERO.checked_sub(U256::ONE), None);
+        assert_eq!(!U256::ZERO, U256::MAX);
+    }
+    #[test]
+    fn shifts_move_bits_across_limbs() {
+        let value = U256::from_u64(0xff);
+
+        assert_eq!((value << 60) >> 60, value, "Shifts should invert");
+        assert_eq!((value << 200).bits(), 208);
+        assert_eq!((value << 252).bits(), 256, "Upper bits are kept");
+        assert_eq!(value << 256, U256::ZERO, "Everything is shifted out");
+        assert_eq!(U256::MAX >> 255, U256::ONE);
+    }
+    #[test]
+    fn division_rounds_down() {
+        let dividend = U256::from_u64(1_000) << 100;
+
+        assert_eq!(
+            dividend.checked_div(U256::from_u64(8) << 100),
+            Some(U256::from_u64(125))
+        );
+        assert_eq!(
+            U256::from_u64(7).checked_div(U256::from_u64(2)),
+            Some(U256::from_u64(3))
+        );
+        assert_eq!(U256::ONE.checked_div(U256::ZERO), None);
+        assert_eq!(
+            U256::MAX.checked_div(U256::MAX),
+            Some(U256::ONE),
+            "Division should work for the full range"
+        );
+    }
+    #[test]
+    fn mul_div_keeps_precision() {
+        let value = U256::MAX >> 2;
+
+        assert_eq!(
+            value.mul_div(4, 4),
+            Some(value),
+            "Intermediate product may exceed 256 bits"
+        );
+        assert_eq!(value.mul_div(8, 1), None, "Result exceeds 256 bits");
+        assert_eq!(U256::from_u64(10).mul_div(3, 4), Some(U256::from_u64(7)));
+        assert_eq!(value.mul_div(1, 0), None);
+    }
+}
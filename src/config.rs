@@ -1,3 +1,5 @@
+use crate::common::address::Network;
+use crate::errors::config_errors::ConfigError;
 use serde::Deserialize;
 use std::{env, fs, path::Path};
 
@@ -7,16 +9,25 @@ pub struct TokenConfig {
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
-    pub total_supply: u64,
+    pub total_supply: u64, // Maximum circulating supply (in smallest units)
 }
 
 /// Configuration for the blockchain.
 #[derive(Debug, Deserialize, Clone)]
 pub struct BlockchainConfig {
+    pub network: Network, // Network of the addresses the chain accepts (mainnet or testnet)
     pub genesis_hash: String,
-    pub difficulty: usize,
-    pub genesis_pre_mined: u64,
+    pub genesis_bits: u32, // Compact target of the genesis block and the easiest target allowed
+    pub genesis_pre_mined: u64, // Amount credited to the genesis miner (in smallest units)
     pub genesis_miner: String,
+    pub initial_subsidy: u64, // Coinbase reward of the first blocks (in smallest units)
+    pub halving_interval: u32, // Number of blocks after which the subsidy halves (0 disables halving)
+    pub target_block_interval: u64, // Desired number of seconds between two blocks
+    pub retarget_interval: u32, // Number of blocks between target adjustments (0 disables retargeting)
+    pub max_mempool_size: usize, // Maximum number of pending transactions
+    pub mempool_ttl: u64, // Seconds a transaction may stay in the mempool (0 disables the TTL)
+    pub max_block_size: usize, // Maximum size of an encoded block in bytes
+    pub max_block_transactions: usize, // Maximum number of transactions per block, coinbase included
 }
 
 /// Main configuration struct combining token and blockchain settings.
@@ -28,28 +39,23 @@ pub struct Config {
 
 impl Config {
     /// Loads configuration from a specified path.
-    pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Self, String> {
-        let contents = fs::read_to_string(&path).map_err(|e| {
-            format!(
-                "Could not read the config file at {:?}: {}",
-                path.as_ref(),
-                e
-            )
+    pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(&path).map_err(|e| ConfigError::Read {
+            path: path.as_ref().display().to_string(),
+            message: e.to_string(),
         })?;
 
-        serde_yaml::from_str(&contents).map_err(|e| {
-            format!(
-                "Could not parse the config file at {:?}: {}",
-                path.as_ref(),
-                e
-            )
+        serde_yaml::from_str(&contents).map_err(|e| ConfigError::Parse {
+            path: path.as_ref().display().to_string(),
+            message: e.to_string(),
         })
     }
 
     /// Loads configuration from a `config.yml` file in the current directory.
-    pub fn load() -> Result<Self, String> {
-        let mut config_path =
-            env::current_dir().map_err(|e| format!("Unable to read current directory: {}", e))?;
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config_path = env::current_dir().map_err(|e| ConfigError::CurrentDirectory {
+            message: e.to_string(),
+        })?;
         config_path.push("config.yml");
         Self::load_from_path(config_path)
     }
@@ -58,6 +64,7 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::blockchain::Blockchain;
     use std::fs;
     use tempfile::NamedTempFile;
     #[test]
@@ -69,10 +76,19 @@ token:
   decimals: 8
   total_supply: 1000000000
 blockchain:
+  network: testnet
   genesis_hash: "00000000000000000000000000000000"
-  difficulty: 2
+  genesis_bits: 0x2000ffff
   genesis_pre_mined: 500000000
   genesis_miner: "Miner1"
+  initial_subsidy: 5000000000
+  halving_interval: 210000
+  target_block_interval: 600
+  retarget_interval: 2016
+  max_mempool_size: 5000
+  mempool_ttl: 1209600
+  max_block_size: 1000000
+  max_block_transactions: 4000
 "#;
         let temp_file = NamedTempFile::new().expect("Failed to create temp file");
         fs::write(temp_file.path(), config_content).expect("Failed to write to temp file");
@@ -89,9 +105,36 @@ blockchain:
             config.blockchain.genesis_hash,
             "00000000000000000000000000000000"
         );
-        assert_eq!(config.blockchain.difficulty, 2);
+        assert_eq!(config.blockchain.genesis_bits, 0x2000_ffff);
         assert_eq!(config.blockchain.genesis_pre_mined, 500_000_000);
+        assert_eq!(config.blockchain.network, Network::Testnet);
         assert_eq!(config.blockchain.genesis_miner, "Miner1");
+        assert_eq!(config.blockchain.initial_subsidy, 5_000_000_000);
+        assert_eq!(config.blockchain.halving_interval, 210_000);
+        assert_eq!(config.blockchain.max_mempool_size, 5_000);
+        assert_eq!(config.blockchain.mempool_ttl, 1_209_600);
+        assert_eq!(config.blockchain.max_block_size, 1_000_000);
+        assert_eq!(config.blockchain.max_block_transactions, 4_000);
+        assert_eq!(config.blockchain.target_block_interval, 600);
+        assert_eq!(config.blockchain.retarget_interval, 2016);
+    }
+    #[test]
+    fn shipped_config_builds_a_blockchain() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config.yml.dist");
+
+        let config = Config::load_from_path(path).unwrap();
+        let blockchain = Blockchain::new(config.clone());
+
+        assert!(
+            blockchain.is_ok(),
+            "config.yml.dist should be usable as is: {:?}",
+            blockchain.err()
+        );
+        assert_eq!(
+            config.blockchain.genesis_pre_mined * 10,
+            config.token.total_supply,
+            "The pre-mine should be 10% of the total supply, both in smallest units"
+        );
     }
     #[test]
     fn missing_yaml_config_file_returns_error() {
@@ -101,9 +144,8 @@ blockchain:
             result.is_err(),
             "Loading a non-existent config file should return an error"
         );
-        let error_message = result.unwrap_err();
         assert!(
-            error_message.contains("Could not read the config file"),
+            matches!(result, Err(ConfigError::Read { .. })),
             "Error message should indicate that the config file is missing"
         );
     }
@@ -116,10 +158,19 @@ token:
   decimals: "invalid" # Invalid type
   total_supply: 1000000000
 blockchain:
+  network: testnet
   genesis_hash: "00000000000000000000000000000000"
-  difficulty: 2
+  genesis_bits: 0x2000ffff
   genesis_pre_mined: 500000000
   genesis_miner: "Miner1"
+  initial_subsidy: 5000000000
+  halving_interval: 210000
+  target_block_interval: 600
+  retarget_interval: 2016
+  max_mempool_size: 5000
+  mempool_ttl: 1209600
+  max_block_size: 1000000
+  max_block_transactions: 4000
 "#;
         let temp_file = NamedTempFile::new().expect("Failed to create temp file");
         fs::write(temp_file.path(), config_content).expect("Failed to write to temp file");
@@ -130,9 +181,8 @@ blockchain:
             result.is_err(),
             "Loading an invalid YAML config file should return an error"
         );
-        let error_message = result.unwrap_err();
         assert!(
-            error_message.contains("Could not parse the config file"),
+            matches!(result, Err(ConfigError::Parse { .. })),
             "Error message should indicate invalid YAML parsing"
         );
     }
@@ -145,10 +195,19 @@ token:
   decimals: 8
   total_supply: 1000000000
 blockchain:
+  network: testnet
   genesis_hash: "00000000000000000000000000000000"
-  difficulty: 2
+  genesis_bits: 0x2000ffff
   genesis_pre_mined: 500000000
   genesis_miner: "Miner1"
+  initial_subsidy: 5000000000
+  halving_interval: 210000
+  target_block_interval: 600
+  retarget_interval: 2016
+  max_mempool_size: 5000
+  mempool_ttl: 1209600
+  max_block_size: 1000000
+  max_block_transactions: 4000
 "#;
         let temp_file = NamedTempFile::new().expect("Failed to create temp file");
         fs::write(temp_file.path(), config_content).expect("Failed to write to temp file");
@@ -159,9 +218,8 @@ blockchain:
             result.is_err(),
             "Loading a config file with a missing key should return an error"
         );
-        let error_message = result.unwrap_err();
         assert!(
-            error_message.contains("Could not parse the config file"),
+            matches!(result, Err(ConfigError::Parse { .. })),
             "Error message should indicate a missing key during YAML parsing"
         );
     }
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+/// Whether an address sent or received the transaction of a history entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received, // Including coinbase transactions
+}
+
+/// A transaction of the main chain as seen by one of its addresses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressEntry {
+    pub id: String,    // Id of the transaction
+    pub height: u32,   // Height of the block holding the transaction
+    pub position: u32, // Position within the block's transactions
+    pub direction: Direction,
+    pub amount: u64,
+    pub fee: u64,     // Paid by the sender on top of the amount
+    pub balance: u64, // Balance of the address right after the transaction
+}
+
+/// Page of an address history, newest entries first.
+/// - Only entries from blocks within `from_height..=to_height` are included.
+/// - The first `offset` of those are skipped and at most `limit` are returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryQuery {
+    pub from_height: u32,
+    pub to_height: u32,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Default for HistoryQuery {
+    /// Queries the entire history.
+    fn default() -> Self {
+        HistoryQuery {
+            from_height: 0,
+            to_height: u32::MAX,
+            offset: 0,
+            limit: usize::MAX,
+        }
+    }
+}
+
+/// History entries of the main chain by address, oldest first.
+/// Entries are added as blocks are connected and removed as they are disconnected, so a
+/// query never scans more than the history of its address.
+#[derive(Debug, Default)]
+pub struct AddressIndex {
+    entries: HashMap<String, Vec<AddressEntry>>,
+}
+
+impl AddressIndex {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        AddressIndex::default()
+    }
+
+    /// Returns the number of addresses with a history.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no address has a history.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Appends an entry to the history of the address.
+    /// Entries must be added in the order their transactions are executed.
+    pub fn insert(&mut self, address: &str, entry: AddressEntry) {
+        self.entries
+            .entry(address.to_string())
+            .or_default()
+            .push(entry);
+    }
+
+    /// Removes the entries of the block at the given height from the history of the given
+    /// addresses; the block must be the last one added.
+    pub fn remove_block<'a>(&mut self, height: u32, addresses: impl IntoIterator<Item = &'a str>) {
+        for address in addresses {
+            let Some(entries) = self.entries.get_mut(address) else {
+                continue;
+            };
+            while entries.last().is_some_and(|entry| entry.height == height) {
+                entries.pop();
+            }
+            if entries.is_empty() {
+                self.entries.remove(address);
+            }
+        }
+    }
+
+    /// Returns the entire history of the address, oldest first.
+    pub fn entries(&self, address: &str) -> &[AddressEntry] {
+        self.entries.get(address).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns a page of the history of the address (see `HistoryQuery`).
+    pub fn history(&self, address: &str, query: &HistoryQuery) -> Vec<&AddressEntry> {
+        let entries = self.entries(address);
+        let start = entries.partition_point(|entry| entry.height < query.from_height);
+        let end = entries.partition_point(|entry| entry.height <= query.to_height);
+        entries[start..end.max(start)]
+            .iter()
+            .rev()
+            .skip(query.offset)
+            .take(query.limit)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(height: u32, balance: u64) -> AddressEntry {
+        AddressEntry {
+            id: format!("tx{}", height),
+            height,
+            position: 1,
+            direction: Direction::Received,
+            amount: 10,
+            fee: 0,
+            balance,
+        }
+    }
+    #[test]
+    fn history_pages_newest_first_within_heights() {
+        // Arrange: One entry per height from 1 to 5
+        let mut index = AddressIndex::new();
+        for height in 1..=5 {
+            index.insert("Alice", entry(height, height as u64 * 10));
+        }
+
+        // Act & Assert
+        let heights = |query: HistoryQuery| -> Vec<u32> {
+            index
+                .history("Alice", &query)
+                .iter()
+                .map(|entry| entry.height)
+                .collect()
+        };
+        assert_eq!(heights(HistoryQuery::default()), vec![5, 4, 3, 2, 1]);
+        assert_eq!(
+            heights(HistoryQuery {
+                from_height: 2,
+                to_height: 4,
+                ..HistoryQuery::default()
+            }),
+            vec![4, 3, 2],
+            "Only entries within the heights should be returned"
+        );
+        assert_eq!(
+            heights(HistoryQuery {
+                offset: 1,
+                limit: 2,
+                ..HistoryQuery::default()
+            }),
+            vec![4, 3],
+            "Pages should skip the offset and stop at the limit"
+        );
+        assert_eq!(
+            heights(HistoryQuery {
+                from_height: 4,
+                to_height: 2,
+                ..HistoryQuery::default()
+            }),
+            Vec::<u32>::new()
+        );
+        assert!(index.history("Bob", &HistoryQuery::default()).is_empty());
+    }
+    #[test]
+    fn removed_blocks_leave_the_history() {
+        let mut index = AddressIndex::new();
+        index.insert("Alice", entry(1, 10));
+        index.insert("Alice", entry(2, 20));
+        index.insert("Bob", entry(2, 10));
+
+        index.remove_block(2, ["Alice", "Bob"]);
+
+        assert_eq!(index.entries("Alice"), &[entry(1, 10)]);
+        assert_eq!(index.len(), 1, "Addresses without history are dropped");
+    }
+}
@@ -1,62 +1,169 @@
 use crate::common::calculate_hash::calculate_block_hash;
+use crate::common::encoding::{Decoder, Encoder};
+use crate::common::merkle::{calculate_merkle_root, merkle_proof, transaction_hashes, MerkleProof};
+use crate::common::target::hash_meets_target;
+use crate::core::miner::Miner;
 use crate::core::transaction::Transaction;
+use crate::errors::encoding_errors::DecodeError;
 use chrono::prelude::*;
 
 /// Represents a block in the blockchain.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Block {
     pub index: u32,
     pub timestamp: String,
     pub transactions: Vec<Transaction>,
+    pub merkle_root: String, // Root of the Merkle tree over the transaction hashes
     pub previous_hash: String,
+    pub beneficiary: String, // Address credited with the fees of the block's transactions
+    pub bits: u32,           // Compact encoding of the target the hash must not exceed
     pub hash: String,
     pub nonce: u64,
 }
 
 impl Block {
-    /// Creates a new block and mines it to meet the difficulty target.
+    /// Creates a new block and mines it with a single worker thread to meet the target
+    /// encoded in the bits (see `Miner`).
+    /// Panics if the bits do not encode a valid target.
     pub fn new(
         index: u32,
         transactions: Vec<Transaction>,
         previous_hash: String,
-        difficulty: usize,
+        beneficiary: String,
+        bits: u32,
+    ) -> Self {
+        let block = Block::template(index, transactions, previous_hash, beneficiary, bits);
+        Miner::new(1)
+            .mine(block)
+            .expect("A miner that gives out no cancel handle is never cancelled")
+    }
+
+    /// Creates a new block timestamped now, which still has to be mined (see `Miner`).
+    pub fn template(
+        index: u32,
+        transactions: Vec<Transaction>,
+        previous_hash: String,
+        beneficiary: String,
+        bits: u32,
     ) -> Self {
         let timestamp = Utc::now().to_rfc3339();
+        let merkle_root = calculate_merkle_root(&transactions);
         let mut block = Block {
             index,
             timestamp,
             transactions,
+            merkle_root,
             previous_hash,
+            beneficiary,
+            bits,
             hash: String::new(),
             nonce: 0,
         };
 
-        block.hash = calculate_block_hash(
-            index,
-            &block.timestamp,
-            &block.transactions,
-            &block.previous_hash,
-            block.nonce,
-        );
-
-        block.mine(difficulty);
+        block.hash = block.calculate_hash();
         block
     }
 
-    /// Mines the block by adjusting the nonce until the hash meets the difficulty target.
-    fn mine(&mut self, difficulty: usize) {
-        let target = "0".repeat(difficulty);
-        while !self.hash.starts_with(&target) {
-            self.nonce += 1;
-            self.hash = calculate_block_hash(
-                self.index,
-                &self.timestamp,
-                &self.transactions,
-                &self.previous_hash,
-                self.nonce,
-            );
+    /// Calculates the hash of the block's header (see `calculate_block_hash`).
+    pub fn calculate_hash(&self) -> String {
+        calculate_block_hash(
+            self.index,
+            &self.timestamp,
+            &self.merkle_root,
+            &self.previous_hash,
+            &self.beneficiary,
+            self.bits,
+            self.nonce,
+        )
+    }
+
+    /// Returns `true` if the block's hash, read as a number, does not exceed its target.
+    pub fn meets_target(&self) -> bool {
+        hash_meets_target(&self.hash, self.bits)
+    }
+
+    /// Returns the block's timestamp in UTC, keeping its sub-second precision.
+    /// Returns `None` if the timestamp is not in RFC 3339 format.
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    }
+
+    /// Returns the block's timestamp in seconds since the Unix epoch (see `time`).
+    pub fn unix_time(&self) -> Option<i64> {
+        self.time().map(|time| time.timestamp())
+    }
+
+    /// Returns the sum of the fees of all transactions in the block.
+    pub fn total_fees(&self) -> u64 {
+        self.transactions
+            .iter()
+            .map(|transaction| transaction.fee)
+            .sum()
+    }
+
+    /// Returns the canonical encoding of the block, including its transactions.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.put_u32(self.index);
+        encoder.put_str(&self.timestamp);
+        encoder.put_str(&self.merkle_root);
+        encoder.put_str(&self.previous_hash);
+        encoder.put_str(&self.beneficiary);
+        encoder.put_u32(self.bits);
+        encoder.put_u64(self.nonce);
+        encoder.put_str(&self.hash);
+        encoder.put_u32(self.transactions.len() as u32);
+        for transaction in &self.transactions {
+            encoder.put_bytes(&transaction.encode());
         }
-        println!("Block mined: {}", self.hash);
+        encoder.finish()
+    }
+
+    /// Returns the number of bytes the transaction adds to the encoding of a block.
+    pub fn encoded_transaction_size(transaction: &Transaction) -> usize {
+        4 + transaction.encode().len() // Length prefix and encoding
+    }
+
+    /// Decodes a block from its canonical encoding.
+    /// Only the format is checked; the chain validates hashes and transactions.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut decoder = Decoder::new(bytes)?;
+        let index = decoder.get_u32()?;
+        let timestamp = decoder.get_str()?;
+        let merkle_root = decoder.get_str()?;
+        let previous_hash = decoder.get_str()?;
+        let beneficiary = decoder.get_str()?;
+        let bits = decoder.get_u32()?;
+        let nonce = decoder.get_u64()?;
+        let hash = decoder.get_str()?;
+        let transaction_count = decoder.get_u32()?;
+        let mut transactions: Vec<Transaction> = Vec::new();
+        for _ in 0..transaction_count {
+            transactions.push(Transaction::decode(decoder.get_bytes()?)?);
+        }
+        decoder.finish()?;
+
+        Ok(Block {
+            index,
+            timestamp,
+            transactions,
+            merkle_root,
+            previous_hash,
+            beneficiary,
+            bits,
+            hash,
+            nonce,
+        })
+    }
+
+    /// Builds the Merkle inclusion proof for the transaction with the given hash.
+    /// Returns `None` if the block does not contain the transaction.
+    pub fn merkle_proof(&self, transaction_hash: &str) -> Option<MerkleProof> {
+        let hashes = transaction_hashes(&self.transactions);
+        let index = hashes.iter().position(|hash| hash == transaction_hash)?;
+        merkle_proof(&hashes, index)
     }
 }
 
@@ -64,22 +171,22 @@ impl Block {
 mod tests {
     use super::*;
     use crate::core::transaction::Transaction;
+    use crate::test_utils::EASY_BITS;
     #[test]
     fn new_block_has_correct_properties() {
         let index = 1;
         let transactions = vec![
-            Transaction::new("Alice".to_string(), "Bob".to_string(), 100),
-            Transaction::new("Charlie".to_string(), "Dave".to_string(), 50),
+            Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 0, 0),
+            Transaction::new("Charlie".to_string(), "Dave".to_string(), 50, 0, 0),
         ];
         let previous_hash =
             "0000000000000000000000000000000000000000000000000000000000000000".to_string();
-        let difficulty = 2;
-
         let block = Block::new(
             index,
             transactions.clone(),
             previous_hash.clone(),
-            difficulty,
+            "Miner".to_string(),
+            EASY_BITS,
         );
 
         // Verify basic properties
@@ -92,31 +199,189 @@ mod tests {
             block.previous_hash, previous_hash,
             "Previous hash should match"
         );
+        assert_eq!(
+            block.merkle_root,
+            calculate_merkle_root(&transactions),
+            "Merkle root should cover the transactions"
+        );
+        assert_eq!(block.beneficiary, "Miner", "Beneficiary should match");
+        assert_eq!(block.bits, EASY_BITS, "Bits should be stored");
         assert_ne!(block.hash, "", "Block hash should not be empty");
     }
     #[test]
+    fn block_encoding_round_trips() {
+        let transactions = vec![
+            Transaction::coinbase("Miner".to_string(), 5_000, 1),
+            Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 1, 0),
+        ];
+        let previous_hash =
+            "0000000000000000000000000000000000000000000000000000000000000000".to_string();
+        let block = Block::new(
+            1,
+            transactions,
+            previous_hash,
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+
+        assert_eq!(
+            Block::decode(&block.encode()),
+            Ok(block.clone()),
+            "Decoding the encoding should return the original block"
+        );
+
+        let empty = Block::new(
+            0,
+            vec![],
+            "GENESIS".to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+        assert_eq!(
+            Block::decode(&empty.encode()),
+            Ok(empty.clone()),
+            "Blocks without transactions should round trip"
+        );
+    }
+    #[test]
+    fn malformed_block_encoding_is_rejected() {
+        let transactions = vec![Transaction::new(
+            "Alice".to_string(),
+            "Bob".to_string(),
+            100,
+            0,
+            0,
+        )];
+        let block = Block::new(
+            1,
+            transactions,
+            "GENESIS".to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+        let bytes = block.encode();
+
+        assert_eq!(
+            Block::decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd),
+            "Truncated encoding should be rejected"
+        );
+
+        let mut extended = bytes.clone();
+        extended.extend_from_slice(&[0, 0]);
+        assert_eq!(
+            Block::decode(&extended),
+            Err(DecodeError::TrailingBytes { count: 2 }),
+            "Encoding with trailing bytes should be rejected"
+        );
+    }
+    #[test]
+    fn merkle_proof_for_included_transaction_verifies() {
+        let transactions = vec![
+            Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 0, 0),
+            Transaction::new("Charlie".to_string(), "Dave".to_string(), 50, 0, 0),
+            Transaction::new("Eve".to_string(), "Frank".to_string(), 25, 0, 0),
+        ];
+        let previous_hash =
+            "0000000000000000000000000000000000000000000000000000000000000000".to_string();
+        let block = Block::new(
+            1,
+            transactions.clone(),
+            previous_hash,
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+
+        // Every transaction of the block can be proven against the Merkle root alone
+        for (index, transaction) in transactions.iter().enumerate() {
+            let proof = block.merkle_proof(&transaction.hash()).unwrap();
+            assert_eq!(proof.index, index, "Proof should point at the transaction");
+            assert!(
+                proof.verify(&transaction.hash(), &block.merkle_root),
+                "Proof should verify against the block's Merkle root"
+            );
+        }
+
+        // A transaction that is not in the block has no proof
+        let missing = Transaction::new("Alice".to_string(), "Bob".to_string(), 1, 0, 0);
+        assert_eq!(
+            block.merkle_proof(&missing.hash()),
+            None,
+            "Missing transaction should have no proof"
+        );
+    }
+    #[test]
+    fn total_fees_sums_transaction_fees() {
+        let transactions = vec![
+            Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 3, 0),
+            Transaction::new("Charlie".to_string(), "Dave".to_string(), 50, 4, 0),
+        ];
+        let previous_hash =
+            "0000000000000000000000000000000000000000000000000000000000000000".to_string();
+
+        let block = Block::new(
+            1,
+            transactions,
+            previous_hash,
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+
+        assert_eq!(block.total_fees(), 7, "Total fees should sum all fees");
+    }
+    #[test]
     fn mining_generates_valid_block() {
-        // Arrange: Create a block with a low difficulty to ensure quick mining
+        // Arrange: Create a block with a target as high as 2 leading zero hex digits
         let index = 1;
         let transactions = vec![Transaction::new(
             "Alice".to_string(),
             "Bob".to_string(),
             100,
+            0,
+            0,
         )];
         let previous_hash =
             "0000000000000000000000000000000000000000000000000000000000000000".to_string();
-        let difficulty = 2; // Low difficulty for test efficiency
+        let bits = 0x2000_ffff;
 
         // Create and mine the block
-        let block = Block::new(index, transactions, previous_hash, difficulty);
+        let block = Block::new(
+            index,
+            transactions,
+            previous_hash,
+            "Miner".to_string(),
+            bits,
+        );
 
-        // Verify that the block's hash meets the difficulty target
-        let target = "0".repeat(difficulty);
+        // Verify that the block's hash meets the target
         assert!(
-            block.hash.starts_with(&target),
-            "Block hash should start with {} zeros to meet difficulty",
-            difficulty
+            block.hash.starts_with("00"),
+            "Block hash should start with 2 zeros to meet target {:#x}",
+            bits
         );
+        assert!(block.meets_target(), "Mined block meets its target");
+        assert_eq!(block.hash, block.calculate_hash(), "Hash covers the header");
+    }
+    #[test]
+    fn block_hash_above_target_does_not_meet_it() {
+        let mut block = Block::new(
+            1,
+            vec![],
+            "GENESIS".to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+
+        block.bits = 0x2000_ffff;
+        block.hash = format!("00fffe{}", "f".repeat(58));
+        assert!(block.meets_target(), "Hash just below the target meets it");
+
+        block.hash = format!("0100{}", "0".repeat(60));
+        assert!(!block.meets_target(), "Hash above the target does not");
+
+        block.bits = 0;
+        block.hash = "0".repeat(64);
+        assert!(!block.meets_target(), "Invalid bits can never be met");
     }
     #[test]
     fn block_hash_changes_with_nonce() {
@@ -125,22 +390,28 @@ mod tests {
             "Alice".to_string(),
             "Bob".to_string(),
             100,
+            0,
+            0,
         )];
         let previous_hash =
             "0000000000000000000000000000000000000000000000000000000000000000".to_string();
-        let difficulty = 1;
-
-        let mut block = Block::new(index, transactions, previous_hash, difficulty);
+        let mut block = Block::new(
+            index,
+            transactions,
+            previous_hash,
+            "Miner".to_string(),
+            EASY_BITS,
+        );
 
-        // Store the initial hash and mine again to change the nonce
+        // Store the initial hash and move on to the next nonce
         let initial_hash = block.hash.clone();
-        block.hash = "".parse().unwrap();
-        block.mine(difficulty);
+        block.nonce += 1;
+        block.hash = block.calculate_hash();
 
-        // Assert: Verify that the hash has changed after mining
+        // Assert: Verify that the hash has changed with the nonce
         assert_ne!(
             block.hash, initial_hash,
-            "Hash should change after mining with a different nonce"
+            "Hash should change with a different nonce"
         );
     }
     #[test]
@@ -150,6 +421,8 @@ mod tests {
             "Alice".to_string(),
             "Bob".to_string(),
             100,
+            0,
+            0,
         )];
         let previous_hash =
             "0000000000000000000000000000000000000000000000000000000000000000".to_string();
@@ -159,31 +432,41 @@ mod tests {
             index,
             timestamp: timestamp.clone(),
             transactions: transactions.clone(),
+            merkle_root: calculate_merkle_root(&transactions),
             previous_hash: previous_hash.clone(),
+            beneficiary: "Miner".to_string(),
+            bits: EASY_BITS,
             hash: String::new(),
             nonce: 0,
         };
         block1.hash = calculate_block_hash(
             index,
             &block1.timestamp,
-            &block1.transactions,
+            &block1.merkle_root,
             &block1.previous_hash,
+            &block1.beneficiary,
+            block1.bits,
             block1.nonce,
         );
 
         let mut block2 = Block {
             index,
             timestamp,
+            merkle_root: calculate_merkle_root(&transactions),
             transactions,
             previous_hash,
+            beneficiary: "Miner".to_string(),
+            bits: EASY_BITS,
             hash: String::new(),
             nonce: 0,
         };
         block2.hash = calculate_block_hash(
             index,
             &block2.timestamp,
-            &block2.transactions,
+            &block2.merkle_root,
             &block2.previous_hash,
+            &block2.beneficiary,
+            block2.bits,
             block2.nonce,
         );
 
@@ -202,16 +485,28 @@ mod tests {
             "Alice".to_string(),
             "Bob".to_string(),
             100,
+            0,
+            0,
         )];
         let previous_hash1 =
             "0000000000000000000000000000000000000000000000000000000000000000".to_string();
         let previous_hash2 =
             "1111111111111111111111111111111111111111111111111111111111111111".to_string();
-        let difficulty = 2;
-
         // Create two blocks with different inputs
-        let block1 = Block::new(index1, transactions.clone(), previous_hash1, difficulty);
-        let block2 = Block::new(index2, transactions.clone(), previous_hash2, difficulty);
+        let block1 = Block::new(
+            index1,
+            transactions.clone(),
+            previous_hash1,
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+        let block2 = Block::new(
+            index2,
+            transactions.clone(),
+            previous_hash2,
+            "Miner".to_string(),
+            EASY_BITS,
+        );
 
         assert_ne!(
             block1.hash, block2.hash,
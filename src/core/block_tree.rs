@@ -0,0 +1,116 @@
+use crate::core::block::Block;
+use std::collections::HashMap;
+
+/// Where a received block ended up in the chain.
+#[derive(Debug, PartialEq)]
+pub enum BlockStatus {
+    /// The block extends the tip of the main chain.
+    Connected,
+    /// The block is kept on a side branch with less work than the main chain.
+    SideBranch,
+    /// The block completes a branch with more work than the main chain, which replaced the
+    /// given number of main chain blocks with the given number of branch blocks.
+    Reorganized { disconnected: u32, connected: u32 },
+}
+
+/// Blocks of competing branches that fork off the main chain.
+/// The main chain itself is held by the `ChainStore`; a branch is found by following the
+/// previous hashes of its blocks until they lead back to the main chain.
+#[derive(Debug, Default)]
+pub struct BlockTree {
+    blocks: HashMap<String, Block>, // Blocks by hash
+}
+
+impl BlockTree {
+    /// Creates an empty tree.
+    pub fn new() -> Self {
+        BlockTree::default()
+    }
+
+    /// Returns the number of blocks on side branches.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Returns `true` if there are no side branches.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Returns `true` if the block with the given hash is on a side branch.
+    pub fn contains(&self, hash: &str) -> bool {
+        self.blocks.contains_key(hash)
+    }
+
+    /// Returns the block with the given hash.
+    pub fn get(&self, hash: &str) -> Option<&Block> {
+        self.blocks.get(hash)
+    }
+
+    /// Adds a block to a side branch.
+    pub fn insert(&mut self, block: Block) {
+        self.blocks.insert(block.hash.clone(), block);
+    }
+
+    /// Removes the block with the given hash and returns it.
+    pub fn remove(&mut self, hash: &str) -> Option<Block> {
+        self.blocks.remove(hash)
+    }
+
+    /// Returns the branch ending at the block with the given hash, oldest block first.
+    /// The branch starts at the first block whose parent is not on a side branch.
+    pub fn branch(&self, hash: &str) -> Vec<&Block> {
+        let mut branch: Vec<&Block> = Vec::new();
+        let mut current = self.blocks.get(hash);
+        while let Some(block) = current {
+            branch.push(block);
+            current = self.blocks.get(&block.previous_hash);
+        }
+        branch.reverse();
+        branch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EASY_BITS;
+
+    fn block(index: u32, previous_hash: &str) -> Block {
+        Block::new(
+            index,
+            vec![],
+            previous_hash.to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        )
+    }
+    #[test]
+    fn branch_follows_previous_hashes_back_to_the_main_chain() {
+        // Arrange: Two blocks forking off main chain block "MAIN", and one off another block
+        let first = block(1, "MAIN");
+        let second = block(2, &first.hash);
+        let other = block(1, "OTHER");
+        let mut tree = BlockTree::new();
+        tree.insert(first.clone());
+        tree.insert(second.clone());
+        tree.insert(other.clone());
+
+        // Act & Assert
+        assert_eq!(
+            tree.branch(&second.hash),
+            vec![&first, &second],
+            "Branch should start at its oldest side block"
+        );
+        assert_eq!(tree.branch(&other.hash), vec![&other]);
+        assert_eq!(tree.branch("unknown"), Vec::<&Block>::new());
+        assert_eq!(tree.len(), 3);
+
+        tree.remove(&first.hash);
+        assert_eq!(
+            tree.branch(&second.hash),
+            vec![&second],
+            "Removed blocks end the branch"
+        );
+    }
+}
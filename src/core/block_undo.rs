@@ -0,0 +1,104 @@
+use crate::common::encoding::{Decoder, Encoder};
+use crate::errors::encoding_errors::DecodeError;
+
+/// State of an account before a block was applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountUndo {
+    pub address: String,
+    pub balance: Option<u64>, // `None` if the block created the account
+    pub nonce: u64,
+}
+
+/// Undo record of a block: the state its transactions changed, as it was before the block
+/// was applied. Restoring it disconnects the block from the account state.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BlockUndo {
+    pub circulating_supply: u64,
+    pub accounts: Vec<AccountUndo>, // Every account the block touched, recorded once
+}
+
+impl BlockUndo {
+    /// Returns `true` if the state of the account is already recorded.
+    pub fn records(&self, address: &str) -> bool {
+        self.accounts
+            .iter()
+            .any(|account| account.address == address)
+    }
+
+    /// Encodes the undo record in the canonical, versioned byte format.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.put_u64(self.circulating_supply);
+        encoder.put_u32(self.accounts.len() as u32);
+        for account in &self.accounts {
+            encoder.put_str(&account.address);
+            encoder.put_presence(account.balance.is_some());
+            if let Some(balance) = account.balance {
+                encoder.put_u64(balance);
+            }
+            encoder.put_u64(account.nonce);
+        }
+        encoder.finish()
+    }
+
+    /// Decodes an undo record from its canonical byte format.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut decoder = Decoder::new(bytes)?;
+        let circulating_supply = decoder.get_u64()?;
+        let count = decoder.get_u32()?;
+        let mut accounts: Vec<AccountUndo> = Vec::new();
+        for _ in 0..count {
+            let address = decoder.get_str()?;
+            let balance = if decoder.get_presence()? {
+                Some(decoder.get_u64()?)
+            } else {
+                None
+            };
+            let nonce = decoder.get_u64()?;
+            accounts.push(AccountUndo {
+                address,
+                balance,
+                nonce,
+            });
+        }
+        decoder.finish()?;
+        Ok(BlockUndo {
+            circulating_supply,
+            accounts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn undo_encoding_round_trips() {
+        let undo = BlockUndo {
+            circulating_supply: 5_000,
+            accounts: vec![
+                AccountUndo {
+                    address: "Alice".to_string(),
+                    balance: Some(100),
+                    nonce: 3,
+                },
+                AccountUndo {
+                    address: "Bob".to_string(),
+                    balance: None,
+                    nonce: 0,
+                },
+            ],
+        };
+
+        let bytes = undo.encode();
+
+        assert_eq!(BlockUndo::decode(&bytes), Ok(undo.clone()));
+        assert!(undo.records("Bob"));
+        assert!(!undo.records("Carol"));
+        assert_eq!(
+            BlockUndo::decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd),
+            "Truncated records should be rejected"
+        );
+    }
+}
@@ -1,29 +1,104 @@
-use crate::common::calculate_hash::calculate_block_hash;
+use crate::common::address::{Address, Network};
+use crate::common::compute_address_from_pub_key::compute_address_from_pub_key;
+use crate::common::merkle::calculate_merkle_root;
+use crate::common::target::{block_work, compact_to_target, retarget};
+use crate::common::u256::U256;
 use crate::config::Config;
+use crate::core::address_index::{AddressEntry, AddressIndex, Direction, HistoryQuery};
 use crate::core::block::*;
+use crate::core::block_tree::{BlockStatus, BlockTree};
+use crate::core::block_undo::{AccountUndo, BlockUndo};
+use crate::core::mempool::Mempool;
+use crate::core::miner::Miner;
 use crate::core::token::Token;
 use crate::core::transaction::Transaction;
+use crate::core::transaction_index::{IndexedTransaction, TransactionIndex};
+use crate::errors::block_validation_errors::BlockValidationError;
+use crate::errors::blockchain_errors::BlockchainError;
+use crate::errors::config_errors::ConfigError;
 use crate::errors::transaction_errors::*;
-use std::collections::HashMap;
+use crate::storage::chain_store::ChainStore;
+use crate::storage::file_store::FileStore;
+use crate::storage::memory_store::MemoryStore;
+use chrono::{SecondsFormat, TimeDelta, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Number of previous blocks whose median time a block's timestamp must exceed.
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// Maximum number of seconds a block's timestamp may be ahead of the local clock.
+pub const MAX_FUTURE_BLOCK_TIME: i64 = 2 * 60 * 60;
 
 /// Represents the blockchain structure.
+/// The blocks and the account state are kept in a `ChainStore`, in memory by default.
 #[derive(Debug)]
-pub struct Blockchain {
-    pub chain: Vec<Block>,
+pub struct Blockchain<S: ChainStore = MemoryStore> {
+    pub store: S,
     pub token: Token,
-    pub mempool: Vec<Transaction>,
-    pub accounts: HashMap<String, u64>,
-    difficulty: usize,
+    pub mempool: Mempool, // Pending transactions, admitted with `submit_transaction`
+    pub miner: Miner,     // Mines the blocks added with `add_block`
+    pub block_tree: BlockTree, // Blocks of competing branches, see `receive_block`
+    pub transaction_index: TransactionIndex, // Locations of the main chain's transactions
+    pub address_index: AddressIndex, // History of the main chain by address
+    pub circulating_supply: u64, // Pre-mined plus minted coins (in smallest units)
+    network: Network,     // Network of the addresses transactions may use
+    genesis_hash: String,
+    genesis_miner: String,
+    genesis_bits: u32, // Target of the genesis block, adjusted every `retarget_interval` blocks
+    genesis_pre_mined: u64,
+    initial_subsidy: u64,
+    halving_interval: u32,
+    target_block_interval: u64,
+    retarget_interval: u32,
+    max_block_size: usize,         // Maximum size of an encoded block in bytes
+    max_block_transactions: usize, // Maximum number of transactions per block, coinbase included
+}
+
+impl Blockchain<MemoryStore> {
+    /// Creates a new Blockchain instance kept in memory (see `with_store`).
+    pub fn new(config: Config) -> Result<Self, BlockchainError> {
+        Blockchain::with_store(config, MemoryStore::new())
+    }
+}
+
+impl Blockchain<FileStore> {
+    /// Opens the blockchain stored in the given data directory (see `with_store`).
+    /// Every block added afterwards is stored before its transactions are executed.
+    pub fn open<P: AsRef<Path>>(config: Config, data_dir: P) -> Result<Self, BlockchainError> {
+        let store = FileStore::open(data_dir)?;
+        Blockchain::with_store(config, store)
+    }
 }
 
-impl Blockchain {
-    /// Creates a new Blockchain instance.
+impl<S: ChainStore> Blockchain<S> {
+    /// Creates a Blockchain instance on top of the given store.
     /// - Initializes the token based on the configuration.
     /// - Sets up accounts with a pre-mined balance.
-    /// - Adds a genesis block to start the chain.
-    pub fn new(config: Config) -> Result<Self, String> {
+    /// - Adds a genesis block to start the chain if the store holds no blocks.
+    /// - Otherwise restores the stored chain (see `restore`).
+    pub fn with_store(config: Config, store: S) -> Result<Self, BlockchainError> {
         if config.token.total_supply < config.blockchain.genesis_pre_mined {
-            return Err("ERR_TOTAL_SUPPLY_LESS_THAN_PRE_MINED".to_string());
+            return Err(ConfigError::TotalSupplyLessThanPreMined {
+                total_supply: config.token.total_supply,
+                pre_mined: config.blockchain.genesis_pre_mined,
+            }
+            .into());
+        }
+        if compact_to_target(config.blockchain.genesis_bits).is_none() {
+            return Err(ConfigError::InvalidGenesisBits {
+                bits: config.blockchain.genesis_bits,
+            }
+            .into());
+        }
+        if let Err(error) =
+            Address::parse_for(&config.blockchain.genesis_miner, config.blockchain.network)
+        {
+            return Err(ConfigError::InvalidGenesisMiner {
+                address: config.blockchain.genesis_miner.clone(),
+                error,
+            }
+            .into());
         }
 
         // Initialize the token based on the provided configuration
@@ -34,124 +109,757 @@ impl Blockchain {
             config.token.total_supply,
         );
 
+        let mut blockchain = Blockchain {
+            store,
+            token,
+            mempool: Mempool::new(
+                config.blockchain.max_mempool_size,
+                config.blockchain.mempool_ttl,
+            ),
+            miner: Miner::default(),
+            block_tree: BlockTree::new(),
+            transaction_index: TransactionIndex::new(),
+            address_index: AddressIndex::new(),
+            circulating_supply: config.blockchain.genesis_pre_mined,
+            network: config.blockchain.network,
+            genesis_hash: config.blockchain.genesis_hash.clone(),
+            genesis_miner: config.blockchain.genesis_miner.clone(),
+            genesis_bits: config.blockchain.genesis_bits,
+            genesis_pre_mined: config.blockchain.genesis_pre_mined,
+            initial_subsidy: config.blockchain.initial_subsidy,
+            halving_interval: config.blockchain.halving_interval,
+            target_block_interval: config.blockchain.target_block_interval,
+            retarget_interval: config.blockchain.retarget_interval,
+            max_block_size: config.blockchain.max_block_size,
+            max_block_transactions: config.blockchain.max_block_transactions,
+        };
+
         // Initialize accounts with a pre-mined balance
-        let mut accounts: HashMap<String, u64> = HashMap::new();
-        accounts.insert(
-            config.blockchain.genesis_miner,
+        blockchain.store.set_balance(
+            &config.blockchain.genesis_miner,
             config.blockchain.genesis_pre_mined,
         );
 
+        if !blockchain.store.is_empty() {
+            blockchain.restore()?;
+            return Ok(blockchain);
+        }
+
         // Create the genesis block
         let genesis_block = Block::new(
             0,
             vec![],
             config.blockchain.genesis_hash,
-            config.blockchain.difficulty,
+            config.blockchain.genesis_miner,
+            config.blockchain.genesis_bits,
         );
+        blockchain.store.append_block(genesis_block)?;
 
-        // Return the new Blockchain instance
-        Ok(Blockchain {
-            chain: vec![genesis_block],
-            accounts,
-            token,
-            mempool: vec![],
-            difficulty: config.blockchain.difficulty,
-        })
+        Ok(blockchain)
+    }
+
+    /// Restores the chain held by the store:
+    /// - Validates the stored chain, including its genesis block (see `validate`).
+    /// - Replays the transactions of every block to rebuild balances, nonces and supply.
+    fn restore(&mut self) -> Result<(), BlockchainError> {
+        self.validate()?;
+        self.replay()
+    }
+
+    /// Rebuilds the account state from the stored blocks:
+    /// - Resets the accounts to the genesis pre-mine.
+    /// - Validates and executes the transactions of every block after the genesis block.
+    /// - Stores the undo records missing for the last blocks, for example after a crash
+    ///   between storing a block and its undo record.
+    fn replay(&mut self) -> Result<(), BlockchainError> {
+        self.store.clear_accounts();
+        self.store
+            .set_balance(&self.genesis_miner, self.genesis_pre_mined);
+        self.circulating_supply = self.genesis_pre_mined;
+        self.transaction_index = TransactionIndex::new();
+        self.address_index = AddressIndex::new();
+
+        for height in 1..self.store.len() as u32 {
+            let block: Block = self.store.block_by_height(height).unwrap().clone();
+            self.validate_block_transactions(&block)?;
+            if self.store.block_undo(height).is_none() {
+                let undo = self.block_undo(&block);
+                self.store.append_undo(undo)?;
+            }
+            self.execute_transactions(&block);
+            self.transaction_index.insert_block(&block);
+        }
+        Ok(())
+    }
+
+    /// Validates the transactions of a block after its coinbase against the account state
+    /// (see `validate_transaction_with_temp_balances`).
+    fn validate_block_transactions(&self, block: &Block) -> Result<(), BlockValidationError> {
+        let mut temp_balances: HashMap<String, u64> = HashMap::new();
+        let mut temp_nonces: HashMap<String, u64> = HashMap::new();
+        for transaction in &block.transactions[1..] {
+            self.validate_transaction_with_temp_balances(
+                transaction,
+                &mut temp_balances,
+                &mut temp_nonces,
+            )
+            .map_err(|error| BlockValidationError::InvalidTransaction {
+                index: block.index,
+                error,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Returns the network of the addresses the chain accepts.
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Returns the coinbase subsidy allowed for the block at the given height.
+    /// - Starts at the configured initial subsidy and halves every `halving_interval` blocks.
+    /// - Does not account for the remaining supply; see `Token::total_supply`.
+    pub fn block_subsidy(&self, height: u32) -> u64 {
+        if self.halving_interval == 0 {
+            return self.initial_subsidy;
+        }
+        let halvings = height / self.halving_interval;
+        if halvings >= u64::BITS {
+            return 0;
+        }
+        self.initial_subsidy >> halvings
+    }
+
+    /// Returns the bits of the target the next block has to meet (see `expected_bits`).
+    pub fn next_bits(&self) -> u32 {
+        let blocks: Vec<&Block> = self.store.blocks().collect();
+        self.expected_bits(&blocks)
+    }
+
+    /// Returns the bits of the target expected of the block following the given blocks.
+    /// - The genesis block has the configured target.
+    /// - Every other block keeps the target of its previous block, except every
+    ///   `retarget_interval` blocks.
+    /// - At a retarget, the target is scaled by the time the last `retarget_interval` blocks
+    ///   took compared with `target_block_interval` per block (see `retarget`). The genesis
+    ///   target is the easiest target allowed.
+    fn expected_bits(&self, previous_blocks: &[&Block]) -> u32 {
+        let Some(previous_block) = previous_blocks.last() else {
+            return self.genesis_bits;
+        };
+        let height = previous_blocks.len();
+        let retarget_interval = self.retarget_interval as usize;
+        if retarget_interval == 0 || !height.is_multiple_of(retarget_interval) {
+            return previous_block.bits;
+        }
+
+        // The first retarget can only look back to the genesis block
+        let first_height = height.saturating_sub(retarget_interval + 1);
+        let (Some(first_time), Some(last_time)) = (
+            previous_blocks[first_height].unix_time(),
+            previous_block.unix_time(),
+        ) else {
+            return previous_block.bits;
+        };
+        let actual_time = last_time.saturating_sub(first_time).max(0) as u64;
+        let target_time = self
+            .target_block_interval
+            .saturating_mul((height - 1 - first_height) as u64);
+
+        let limit = compact_to_target(self.genesis_bits).unwrap_or(U256::MAX);
+        retarget(previous_block.bits, actual_time, target_time, limit)
+    }
+
+    /// Returns the total work of the chain: the sum of the work of its blocks.
+    pub fn chain_work(&self) -> U256 {
+        self.store
+            .blocks()
+            .map(|block| block_work(block.bits))
+            .fold(U256::ZERO, U256::saturating_add)
     }
 
     /// Adds a new block to the blockchain:
-    /// - Processes valid transactions from the mempool.
-    /// - Creates a new block with these transactions.
-    /// - Appends the block to the chain.
-    /// - Validates the entire blockchain after adding the block.
-    /// - Executes the transactions (updates balances).
-    pub fn add_block(&mut self) {
+    /// - Selects valid transactions from the mempool that fit in the block next to its
+    ///   coinbase (see `process_mempool`).
+    /// - Creates a new block with these transactions, crediting its fees to the beneficiary.
+    /// - Mines the block with the miner to meet the target expected at its height.
+    /// - Prepends a coinbase transaction paying the block subsidy to the beneficiary, limited
+    ///   to the supply that is left.
+    /// - Validates the entire blockchain including the new block (see `validate`).
+    /// - Appends the block with its undo record to the store and executes the transactions
+    ///   (see `apply_block`), then removes its transactions from the mempool.
+    ///
+    /// Returns the added block, or why no block was added. The transactions of a block that
+    /// is not added stay in the mempool.
+    pub fn add_block(&mut self, beneficiary: &str) -> Result<Block, BlockchainError> {
+        let bits = self.next_bits();
+        let last_block = self.store.last_block().unwrap();
+        let new_block_index = last_block.index + 1;
+        let previous_hash = last_block.hash.clone();
+
+        let remaining_supply = self
+            .token
+            .total_supply
+            .saturating_sub(self.circulating_supply);
+        let subsidy = self.block_subsidy(new_block_index).min(remaining_supply);
+        let coinbase = Transaction::coinbase(beneficiary.to_string(), subsidy, new_block_index);
+
+        // The header and coinbase take the same space in every block of this height, with the
+        // timestamp at its longest
+        let mut header = Block::template(
+            new_block_index,
+            vec![coinbase.clone()],
+            previous_hash.clone(),
+            beneficiary.to_string(),
+            bits,
+        );
+        header.timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Nanos, false);
+        let available_size = self.max_block_size.saturating_sub(header.encode().len());
+
         // Process the mempool and collect valid transactions
-        let valid_transactions: Vec<Transaction> = self.process_mempool();
+        let valid_transactions: Vec<Transaction> = self.process_mempool(available_size);
 
         if valid_transactions.is_empty() {
-            return; // Never create a new block when there are no transactions
+            // Never create a new block when there are no transactions
+            return Err(BlockchainError::NoValidTransactions);
         }
 
-        let last_block = self.chain.last().unwrap();
-        let new_block_index = last_block.index + 1;
+        let mut block_transactions: Vec<Transaction> = vec![coinbase];
+        block_transactions.extend(valid_transactions);
 
-        let new_block = Block::new(
+        let template = Block::template(
             new_block_index,
-            valid_transactions.clone(),
-            last_block.hash.clone(),
-            self.difficulty,
-        );
+            block_transactions,
+            previous_hash,
+            beneficiary.to_string(),
+            bits,
+        );
+        let Some(new_block) = self.miner.mine(template) else {
+            return Err(BlockchainError::MiningCancelled {
+                index: new_block_index,
+            });
+        };
 
         // Validate block with the network
         // TODO
 
-        self.chain.push(new_block);
+        let blocks: Vec<&Block> = self
+            .store
+            .blocks()
+            .chain(std::iter::once(&new_block))
+            .collect();
+        self.validate_blocks(&blocks)?;
+
+        self.apply_block(new_block.clone())?;
+        self.mempool.remove_included(&new_block.transactions);
+        Ok(new_block)
+    }
+
+    /// Applies a validated block to the tip of the chain:
+    /// - Appends the block to the store, followed by its undo record (see `block_undo`).
+    /// - Pops the block again if its undo record cannot be stored, so every applied block can
+    ///   be disconnected.
+    /// - Executes the transactions of the block.
+    fn apply_block(&mut self, block: Block) -> Result<(), BlockchainError> {
+        let undo = self.block_undo(&block);
+        self.store.append_block(block.clone())?;
+        if let Err(why) = self.store.append_undo(undo) {
+            if let Err(pop_error) = self.store.pop_block() {
+                eprintln!("Could not remove block {}: {}", block.index, pop_error);
+            }
+            return Err(why.into());
+        }
+
+        self.execute_transactions(&block);
+        self.transaction_index.insert_block(&block);
+        Ok(())
+    }
+
+    /// Returns the undo record of a block that is about to be applied: the circulating supply
+    /// and the state of every account its transactions touch, as they are now.
+    fn block_undo(&self, block: &Block) -> BlockUndo {
+        let mut undo = BlockUndo {
+            circulating_supply: self.circulating_supply,
+            accounts: vec![],
+        };
+        let addresses = block
+            .transactions
+            .iter()
+            .flat_map(|transaction| [&transaction.sender, &transaction.receiver])
+            .chain(std::iter::once(&block.beneficiary));
+        for address in addresses {
+            if address.is_empty() || undo.records(address) {
+                continue; // Coinbase transactions have no sender
+            }
+            undo.accounts.push(AccountUndo {
+                address: address.clone(),
+                balance: self.store.balance(address),
+                nonce: self.store.nonce(address),
+            });
+        }
+        undo
+    }
+
+    /// Disconnects the block at the tip of the chain: removes it from the store and restores
+    /// the account state and circulating supply recorded in its undo record.
+    /// The genesis block is never disconnected.
+    fn disconnect_tip(&mut self) -> Result<Block, BlockchainError> {
+        let height = self.store.len().saturating_sub(1) as u32;
+        if height == 0 {
+            return Err(BlockchainError::GenesisCannotBeDisconnected);
+        }
+        let undo: BlockUndo = self
+            .store
+            .block_undo(height)
+            .cloned()
+            .ok_or(BlockchainError::MissingUndo { index: height })?;
+        let block: Block = self.store.pop_block()?.unwrap();
+
+        for account in undo.accounts {
+            match account.balance {
+                Some(balance) => {
+                    self.store.set_balance(&account.address, balance);
+                    if self.store.nonce(&account.address) != account.nonce {
+                        self.store.set_nonce(&account.address, account.nonce);
+                    }
+                }
+                None => self.store.remove_account(&account.address),
+            }
+        }
+        self.circulating_supply = undo.circulating_supply;
+        self.transaction_index.remove_block(&block);
+        let addresses = block
+            .transactions
+            .iter()
+            .flat_map(|transaction| [transaction.sender.as_str(), transaction.receiver.as_str()]);
+        self.address_index.remove_block(block.index, addresses);
+        Ok(block)
+    }
+
+    /// Receives a block mined elsewhere, for example by a peer:
+    /// - Rejects blocks that are already known or do not follow a known block.
+    /// - Validates the block on top of its branch (see `validate_block`).
+    /// - Keeps the block in the block tree if its branch has no more work than the main chain.
+    /// - Otherwise makes its branch the main chain (see `reorganize`).
+    pub fn receive_block(&mut self, block: Block) -> Result<BlockStatus, BlockchainError> {
+        let index = block.index;
+        if self.store.block_by_hash(&block.hash).is_some() || self.block_tree.contains(&block.hash)
+        {
+            return Err(BlockValidationError::DuplicateBlock { index }.into());
+        }
+
+        // The branch consists of the side blocks leading to the block, if any
+        let mut branch: Vec<Block> = self
+            .block_tree
+            .branch(&block.previous_hash)
+            .into_iter()
+            .cloned()
+            .collect();
+        let fork_hash = branch
+            .first()
+            .map_or(&block.previous_hash, |first| &first.previous_hash);
+        let Some(fork_height) = self.store.block_by_hash(fork_hash).map(|fork| fork.index) else {
+            return Err(BlockValidationError::UnknownParent { index }.into());
+        };
+
+        let mut previous_blocks: Vec<&Block> =
+            self.store.blocks().take(fork_height as usize + 1).collect();
+        previous_blocks.extend(branch.iter());
+        let circulating_supply = self.supply_after(&previous_blocks);
+        self.validate_block(&block, &previous_blocks, circulating_supply)?;
+
+        let branch_work = previous_blocks
+            .iter()
+            .chain(std::iter::once(&&block))
+            .map(|block| block_work(block.bits))
+            .fold(U256::ZERO, U256::saturating_add);
+        if branch_work <= self.chain_work() {
+            self.block_tree.insert(block);
+            return Ok(BlockStatus::SideBranch);
+        }
+
+        branch.push(block);
+        self.reorganize(fork_height, branch)
+    }
+
+    /// Returns the circulating supply after the given blocks, which start at the genesis block.
+    fn supply_after(&self, blocks: &[&Block]) -> u64 {
+        blocks
+            .iter()
+            .skip(1)
+            .filter_map(|block| block.transactions.first())
+            .fold(self.genesis_pre_mined, |supply, coinbase| {
+                supply.saturating_add(coinbase.amount)
+            })
+    }
+
+    /// Makes the given branch, which forks off the main chain after the given height, the
+    /// main chain:
+    /// - Disconnects the main chain blocks after the fork point (see `disconnect_tip`).
+    /// - Connects the blocks of the branch, validating their transactions on the way.
+    /// - Keeps the disconnected blocks in the block tree and returns their transactions that
+    ///   are not part of the branch and are still valid to the mempool (see `readmit`).
+    ///
+    /// If a block of the branch holds an invalid transaction, the previous main chain is
+    /// restored and the invalid block and its descendants are dropped from the block tree,
+    /// while the valid blocks before it stay on their side branch. The pending transactions
+    /// the connected blocks of the branch included return to the mempool.
+    fn reorganize(
+        &mut self,
+        fork_height: u32,
+        branch: Vec<Block>,
+    ) -> Result<BlockStatus, BlockchainError> {
+        let disconnected: Vec<Block> = self.disconnect_after(fork_height)?;
+        let mut included_pending: Vec<Transaction> = Vec::new();
+
+        for (position, block) in branch.iter().enumerate() {
+            let pending = block.transactions[1..]
+                .iter()
+                .filter(|transaction| self.mempool.contains(&transaction.id()))
+                .cloned();
+            included_pending.extend(pending);
+            let Err(why) = self.connect_block(block.clone()) else {
+                continue;
+            };
+
+            // Restore the previous main chain
+            self.disconnect_after(fork_height)?;
+            for block in disconnected {
+                self.apply_block(block)?;
+            }
+            self.readmit(included_pending);
+            for valid_block in &branch[..position] {
+                self.block_tree.insert(valid_block.clone());
+            }
+            for invalid_block in &branch[position..] {
+                self.block_tree.remove(&invalid_block.hash);
+            }
+            return Err(why);
+        }
+
+        let status = if disconnected.is_empty() {
+            BlockStatus::Connected
+        } else {
+            BlockStatus::Reorganized {
+                disconnected: disconnected.len() as u32,
+                connected: branch.len() as u32,
+            }
+        };
+
+        // Return the orphaned transactions to the mempool
+        let connected_transactions: Vec<&Transaction> = branch
+            .iter()
+            .flat_map(|block| &block.transactions[1..])
+            .collect();
+        let orphaned: Vec<Transaction> = disconnected
+            .iter()
+            .flat_map(|block| &block.transactions[1..])
+            .filter(|transaction| !connected_transactions.contains(transaction))
+            .cloned()
+            .collect();
+        for block in disconnected {
+            self.block_tree.insert(block);
+        }
+        self.readmit(orphaned);
+
+        Ok(status)
+    }
+
+    /// Returns transactions to the mempool after the main chain changed, in order.
+    /// Transactions that are no longer valid on the main chain, for example because they
+    /// spend a nonce the new chain used, are dropped (see `submit_transaction`).
+    fn readmit(&mut self, transactions: Vec<Transaction>) {
+        for transaction in transactions {
+            let _ = self.submit_transaction(transaction);
+        }
+    }
+
+    /// Disconnects the blocks after the given height and returns them, oldest first.
+    fn disconnect_after(&mut self, height: u32) -> Result<Vec<Block>, BlockchainError> {
+        let mut disconnected: Vec<Block> = Vec::new();
+        while self.store.len() > height as usize + 1 {
+            disconnected.push(self.disconnect_tip()?);
+        }
+        disconnected.reverse();
+        Ok(disconnected)
+    }
+
+    /// Applies a block to the tip of the main chain after validating its transactions and
+    /// removes them from the mempool.
+    fn connect_block(&mut self, block: Block) -> Result<(), BlockchainError> {
+        self.validate_block_transactions(&block)?;
+        self.apply_block(block.clone())?;
+
+        self.block_tree.remove(&block.hash);
+        self.mempool.remove_included(&block.transactions);
+        Ok(())
+    }
+
+    /// Admits a transaction to the mempool after validating it against the chain (see
+    /// `check_transaction`).
+    /// Rejects transactions that the full mempool has no room for (see `Mempool::insert`).
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> Result<(), TransactionError> {
+        self.check_transaction(&transaction)?;
+        self.mempool.insert(transaction)
+    }
+
+    /// Checks that a transaction can be admitted to the mempool, without admitting it:
+    /// - Applies the checks of `validate_transaction_with_temp_balances`, spending the
+    ///   transactions of the sender that are already pending first.
+    /// - Accepts nonces ahead of the sender's next nonce; such transactions are held in the
+    ///   mempool until the transactions before them are mined. Nonces at least the mempool
+    ///   size ahead are rejected, as the transactions before them could never all be pending.
+    /// - Rejects transactions that expire before the next block.
+    /// - Rejects transactions that are already pending.
+    pub fn check_transaction(&self, transaction: &Transaction) -> Result<(), TransactionError> {
+        if self.mempool.contains(&transaction.id()) {
+            return Err(TransactionError::AlreadyInMempool);
+        }
+        check_expiry(transaction, self.store.len() as u32)?;
+
+        let sender = &transaction.sender;
+        let mut temp_balances: HashMap<String, u64> = HashMap::new();
+        let mut temp_nonces: HashMap<String, u64> = HashMap::new();
+        if let Some(balance) = self.store.balance(sender) {
+            temp_balances.insert(
+                sender.clone(),
+                balance.saturating_sub(self.pending_cost(sender)),
+            );
+        }
+        let next_nonce = self.store.nonce(sender);
+        if transaction.nonce.saturating_sub(next_nonce) >= self.mempool.max_size() as u64 {
+            return Err(TransactionError::NonceTooFarAhead {
+                sender: sender.clone(),
+                next: next_nonce,
+                found: transaction.nonce,
+            });
+        }
+        temp_nonces.insert(sender.clone(), transaction.nonce.max(next_nonce));
+        self.validate_transaction_with_temp_balances(
+            transaction,
+            &mut temp_balances,
+            &mut temp_nonces,
+        )
+    }
+
+    /// Returns the amounts plus fees of the sender's pending transactions.
+    pub fn pending_cost(&self, sender: &str) -> u64 {
+        self.mempool
+            .transactions()
+            .iter()
+            .filter(|pending| pending.sender == sender)
+            .fold(0u64, |cost, pending| {
+                cost.saturating_add(pending.amount)
+                    .saturating_add(pending.fee)
+            })
+    }
+
+    /// Returns the nonce of the sender's next transaction: the first nonce from the
+    /// sender's next nonce on that no pending transaction uses.
+    pub fn next_nonce(&self, sender: &str) -> u64 {
+        let pending: HashSet<u64> = self
+            .mempool
+            .transactions()
+            .iter()
+            .filter(|transaction| transaction.sender == sender)
+            .map(|transaction| transaction.nonce)
+            .collect();
+        let mut nonce = self.store.nonce(sender);
+        while pending.contains(&nonce) {
+            nonce += 1;
+        }
+        nonce
+    }
+
+    /// Returns the lowest fee the mempool currently admits (see `Mempool::minimum_fee`).
+    pub fn minimum_fee(&self) -> u64 {
+        self.mempool.minimum_fee()
+    }
 
-        if !self.is_valid() {
+    /// Drops the transactions that can no longer be mined from the mempool, logging why:
+    /// - Transactions waiting longer than the mempool TTL.
+    /// - Transactions that expire before the next block.
+    pub fn prune_mempool(&mut self) {
+        for transaction in self.mempool.remove_stale(Utc::now()) {
             eprintln!(
-                "Blockchain is invalid after adding block {}. Rolling back.",
-                new_block_index
+                "Dropped transaction {} from the mempool: Waited longer than the mempool TTL.",
+                transaction.id()
             );
-            self.chain.pop();
-            return;
         }
 
-        self.execute_transactions(&valid_transactions);
+        let next_height = self.store.len() as u32;
+        let expired: Vec<(String, TransactionError)> = self
+            .mempool
+            .transactions()
+            .iter()
+            .filter_map(|transaction| {
+                let why = check_expiry(transaction, next_height).err()?;
+                Some((transaction.id(), why))
+            })
+            .collect();
+        for (id, why) in expired {
+            self.mempool.remove(&id);
+            eprintln!("Dropped transaction {} from the mempool: {}", id, why);
+        }
     }
 
-    /// Loops through the pending transactions and return the valid ones.
-    fn process_mempool(&mut self) -> Vec<Transaction> {
+    /// Selects the pending transactions that are valid on top of the chain and fit in a block.
+    /// - Transactions paying the highest fee are selected first, lower nonces first on ties.
+    /// - Transactions are selected while their encoding fits in `available_size` bytes and
+    ///   the block stays within `max_block_transactions`. The others stay in the mempool
+    ///   for a later block.
+    /// - Transactions with a nonce ahead of the sender's next nonce are retried once the
+    ///   transactions they depend on are selected, so consecutive transactions of one
+    ///   sender can be included in the same block regardless of their fees.
+    /// - Transactions whose nonce is still ahead stay in the mempool until the missing
+    ///   transactions are mined.
+    /// - Transactions that became invalid since they were admitted, for example because
+    ///   another transaction of the sender was mined, are removed from the mempool.
+    ///
+    /// The mempool is pruned first (see `prune_mempool`). The selected transactions stay in
+    /// the mempool until their block is added.
+    fn process_mempool(&mut self, mut available_size: usize) -> Vec<Transaction> {
+        self.prune_mempool();
+
         let mut valid_transactions: Vec<Transaction> = Vec::new();
-        let mut temp_balances: HashMap<String, u64> = self.accounts.clone();
+        let mut temp_balances: HashMap<String, u64> = HashMap::new();
+        let mut temp_nonces: HashMap<String, u64> = HashMap::new();
+        let mut invalid_transactions: Vec<Transaction> = Vec::new();
+        let max_count = self.max_block_transactions.saturating_sub(1); // Room for the coinbase
+
+        let mut pending: Vec<Transaction> = self.mempool.transactions().to_vec();
+        pending.sort_by(|a, b| b.fee.cmp(&a.fee).then(a.nonce.cmp(&b.nonce)));
+
+        loop {
+            let selected_before = valid_transactions.len();
+            let mut held_transactions: Vec<Transaction> = Vec::new();
+
+            for transaction in pending {
+                let size = Block::encoded_transaction_size(&transaction);
+                if valid_transactions.len() >= max_count || size > available_size {
+                    continue; // Left for a later block
+                }
+                match self.validate_transaction_with_temp_balances(
+                    &transaction,
+                    &mut temp_balances,
+                    &mut temp_nonces,
+                ) {
+                    Ok(()) => {
+                        available_size -= size;
+                        valid_transactions.push(transaction);
+                    }
+                    Err(TransactionError::InvalidNonce {
+                        expected, found, ..
+                    }) if found > expected => held_transactions.push(transaction),
+                    Err(_) => invalid_transactions.push(transaction),
+                }
+            }
 
-        for transaction in &self.mempool {
-            if let Err(why) =
-                self.validate_transaction_with_temp_balances(transaction, &mut temp_balances)
-            {
-                eprintln!("Transaction validation failed: {}", why);
-                continue;
+            pending = held_transactions;
+            if pending.is_empty() || valid_transactions.len() == selected_before {
+                break;
             }
-            valid_transactions.push(transaction.clone());
         }
 
-        self.mempool.clear(); // todo not sure if clearing the mempool here is the right spot
+        self.mempool.remove_included(&invalid_transactions);
         valid_transactions
     }
 
-    /// Executes a list of valid transactions and updates the account balances accordingly.
+    /// Executes the valid transactions of a block and updates the account balances accordingly.
     ///
-    /// This function iterates over the provided list of valid transactions and:
-    /// - Deducts the transaction amount from the sender's balance.
+    /// This function credits the collected fees to the beneficiary, then iterates over the
+    /// block's transactions and:
+    /// - Mints the amount of a coinbase transaction to its receiver.
+    /// - Deducts the transaction amount plus fee from the sender's balance.
     /// - Adds the transaction amount to the receiver's balance.
+    /// - Increments the sender's nonce.
+    /// - Adds the transaction to the history of its sender and receiver in the address index.
+    ///
+    /// As the coinbase pays the beneficiary, its history entry includes the collected fees.
     ///
-    /// It assumes that all transactions in the provided list are already validated and
-    /// no further validation is performed.
-    fn execute_transactions(&mut self, valid_transactions: &Vec<Transaction>) {
-        for transaction in valid_transactions {
-            *self.accounts.entry(transaction.sender.clone()).or_insert(0) -= transaction.amount;
-            *self
-                .accounts
-                .entry(transaction.receiver.clone())
-                .or_insert(0) += transaction.amount;
+    /// It assumes that all transactions of the block are already validated and no further
+    /// validation is performed.
+    fn execute_transactions(&mut self, block: &Block) {
+        let collected_fees: u64 = block
+            .transactions
+            .iter()
+            .filter(|transaction| !transaction.is_coinbase())
+            .map(|transaction| transaction.fee)
+            .sum();
+        if collected_fees > 0 {
+            self.credit(&block.beneficiary, collected_fees);
+        }
+
+        for (position, transaction) in block.transactions.iter().enumerate() {
+            if transaction.is_coinbase() {
+                self.credit(&transaction.receiver, transaction.amount);
+                self.circulating_supply += transaction.amount;
+            } else {
+                let sender_balance = self.store.balance(&transaction.sender).unwrap_or(0);
+                self.store.set_balance(
+                    &transaction.sender,
+                    sender_balance - transaction.amount - transaction.fee,
+                );
+                self.credit(&transaction.receiver, transaction.amount);
+                let sender_nonce = self.store.nonce(&transaction.sender);
+                self.store.set_nonce(&transaction.sender, sender_nonce + 1);
+                self.index_address(block, position, &transaction.sender, Direction::Sent);
+            }
+            self.index_address(block, position, &transaction.receiver, Direction::Received);
         }
     }
 
-    /// Validates a transaction and updates temporary balances if the transaction is valid.
+    /// Adds the transaction at the given position of the block to the history of the address,
+    /// with the address's balance after the transaction.
+    fn index_address(
+        &mut self,
+        block: &Block,
+        position: usize,
+        address: &str,
+        direction: Direction,
+    ) {
+        let transaction = &block.transactions[position];
+        let entry = AddressEntry {
+            id: transaction.id(),
+            height: block.index,
+            position: position as u32,
+            direction,
+            amount: transaction.amount,
+            fee: transaction.fee,
+            balance: self.store.balance(address).unwrap_or(0),
+        };
+        self.address_index.insert(address, entry);
+    }
+
+    /// Adds the amount to the balance of the account, creating the account if needed.
+    fn credit(&mut self, address: &str, amount: u64) {
+        let balance = self.store.balance(address).unwrap_or(0);
+        self.store.set_balance(address, balance + amount);
+    }
+
+    /// Validates a transaction and updates temporary balances and nonces if the transaction is valid.
+    /// The temporary balances and nonces hold the pending changes on top of the store.
     ///
     /// This function performs the following checks:
     /// - Ensures that the sender and receiver addresses are not empty.
     /// - Ensures that the sender and receiver are not the same address.
     /// - Ensures that the transaction amount is greater than zero.
-    /// - Ensures that the sender has sufficient balance in the provided temporary balances.
+    /// - Ensures that both addresses are well-formed addresses of the chain's network.
+    /// - Ensures that the public key hashes to the sender address.
+    /// - Ensures that the signature verifies against the public key.
+    /// - Ensures that the nonce equals the sender's next nonce in the provided temporary nonces.
+    /// - Ensures that the sender has sufficient balance for the amount plus fee in the provided
+    ///   temporary balances.
     ///
-    /// If all validations pass, the transaction amount is deducted from the sender's balance
-    /// and added to the receiver's balance in the provided `temp_balances` map.
+    /// If all validations pass, the transaction amount plus fee is deducted from the sender's
+    /// balance and the transaction amount is added to the receiver's balance in the provided
+    /// `temp_balances` map, and the sender's nonce is incremented in the provided `temp_nonces` map.
     ///
     fn validate_transaction_with_temp_balances(
         &self,
         transaction: &Transaction,
         temp_balances: &mut HashMap<String, u64>,
+        temp_nonces: &mut HashMap<String, u64>,
     ) -> Result<(), TransactionError> {
         if transaction.sender.is_empty() || transaction.receiver.is_empty() {
             return Err(TransactionError::AddressCannotBeEmpty);
@@ -162,90 +870,439 @@ impl Blockchain {
         if transaction.amount == 0 {
             return Err(TransactionError::AmountMustBeGreaterThanZero);
         }
-        let receiver_balance = *temp_balances.entry(transaction.receiver.clone()).or_insert(0);
+        for address in [&transaction.sender, &transaction.receiver] {
+            Address::parse_for(address, self.network).map_err(|error| {
+                TransactionError::InvalidAddress {
+                    address: address.clone(),
+                    error,
+                }
+            })?;
+        }
+        let public_key = transaction
+            .public_key
+            .as_ref()
+            .ok_or(TransactionError::MissingPublicKey)?;
+        if compute_address_from_pub_key(public_key, self.network) != transaction.sender {
+            return Err(TransactionError::PublicKeyDoesNotMatchSender {
+                sender: transaction.sender.clone(),
+            });
+        }
+        if transaction.signature.is_none() {
+            return Err(TransactionError::MissingSignature);
+        }
+        if !transaction.verify(public_key) {
+            return Err(TransactionError::InvalidSignature);
+        }
+        let expected_nonce = temp_nonces
+            .get(&transaction.sender)
+            .copied()
+            .unwrap_or_else(|| self.store.nonce(&transaction.sender));
+        if transaction.nonce != expected_nonce {
+            return Err(TransactionError::InvalidNonce {
+                sender: transaction.sender.clone(),
+                expected: expected_nonce,
+                found: transaction.nonce,
+            });
+        }
+        let following_nonce =
+            expected_nonce
+                .checked_add(1)
+                .ok_or_else(|| TransactionError::InvalidNonce {
+                    sender: transaction.sender.clone(),
+                    expected: expected_nonce,
+                    found: transaction.nonce,
+                })?;
+        let receiver_balance = temp_balances
+            .get(&transaction.receiver)
+            .copied()
+            .or_else(|| self.store.balance(&transaction.receiver))
+            .unwrap_or(0);
         if receiver_balance.checked_add(transaction.amount).is_none() {
             return Err(TransactionError::BalanceOverflow);
         }
-        let sender_balance: &mut u64 = temp_balances.get_mut(&transaction.sender).ok_or(
-            TransactionError::SenderDoesNotExist {
+        let total_cost = transaction
+            .amount
+            .checked_add(transaction.fee)
+            .ok_or(TransactionError::BalanceOverflow)?;
+        let sender_balance: u64 = temp_balances
+            .get(&transaction.sender)
+            .copied()
+            .or_else(|| self.store.balance(&transaction.sender))
+            .ok_or(TransactionError::SenderDoesNotExist {
                 sender: transaction.sender.clone(),
-            },
-        )?;
-        if *sender_balance < transaction.amount {
+            })?;
+        if sender_balance < total_cost {
             return Err(TransactionError::InsufficientBalance {
                 sender: transaction.sender.clone(),
-                requested: transaction.amount,
-                available: *sender_balance,
+                requested: total_cost,
+                available: sender_balance,
             });
         }
 
-        *sender_balance -= transaction.amount;
-        *temp_balances
-            .entry(transaction.receiver.clone())
-            .or_insert(0) += transaction.amount;
+        temp_balances.insert(transaction.sender.clone(), sender_balance - total_cost);
+        temp_balances.insert(
+            transaction.receiver.clone(),
+            receiver_balance + transaction.amount,
+        );
+        temp_nonces.insert(transaction.sender.clone(), following_nonce);
 
         Ok(())
     }
 
-    /// Validates the blockchain integrity.
-    /// Ensures hashes match and blocks are correctly linked.
+    /// Returns `true` if the blockchain passes `validate`.
     pub fn is_valid(&self) -> bool {
-        // Loop starts at 1: Skips the genesis block, as it has no previous block
-        for i in 1..self.chain.len() {
-            let current_block: &Block = &self.chain[i];
-            let previous_block: &Block = &self.chain[i - 1];
-
-            // Verify the previous hash matches the hash of the preceding block
-            if current_block.previous_hash != previous_block.hash {
-                eprintln!("Chain is broken at block {}!", current_block.index);
-                return false;
+        self.validate().is_ok()
+    }
+
+    /// Validates the blockchain integrity and reports the first rule a block breaks.
+    /// - Ensures the genesis block matches the configuration (see `validate_genesis`).
+    /// - Ensures every other block is valid on top of its previous blocks
+    ///   (see `validate_block`).
+    pub fn validate(&self) -> Result<(), BlockValidationError> {
+        let blocks: Vec<&Block> = self.store.blocks().collect();
+        self.validate_blocks(&blocks)
+    }
+
+    /// Validates the given blocks as a chain, starting at the genesis block (see `validate`).
+    fn validate_blocks(&self, blocks: &[&Block]) -> Result<(), BlockValidationError> {
+        let Some(genesis) = blocks.first() else {
+            return Ok(());
+        };
+        self.validate_genesis(genesis)?;
+
+        let mut circulating_supply: u64 = self.genesis_pre_mined;
+        for i in 1..blocks.len() {
+            circulating_supply =
+                self.validate_block(blocks[i], &blocks[..i], circulating_supply)?;
+        }
+        Ok(())
+    }
+
+    /// Ensures the genesis block has index 0, follows the configured genesis hash, is mined
+    /// by the genesis miner for the genesis target and holds no transactions.
+    fn validate_genesis(&self, genesis: &Block) -> Result<(), BlockValidationError> {
+        if genesis.index != 0
+            || genesis.previous_hash != self.genesis_hash
+            || genesis.beneficiary != self.genesis_miner
+            || genesis.bits != self.genesis_bits
+            || !genesis.transactions.is_empty()
+            || genesis.merkle_root != calculate_merkle_root(&genesis.transactions)
+        {
+            return Err(BlockValidationError::GenesisMismatch);
+        }
+        if genesis.hash != genesis.calculate_hash() {
+            return Err(BlockValidationError::InvalidHash { index: 0 });
+        }
+        if genesis.time().is_none() {
+            return Err(BlockValidationError::InvalidTimestamp { index: 0 });
+        }
+        if !genesis.meets_target() {
+            return Err(BlockValidationError::TargetNotMet { index: 0 });
+        }
+        Ok(())
+    }
+
+    /// Validates a block on top of the given previous blocks, which start at the genesis block.
+    /// - Ensures the block follows its previous block in index and hash.
+    /// - Ensures its hash matches its content.
+    /// - Ensures it is timestamped after the median time of the last `MEDIAN_TIME_SPAN`
+    ///   blocks and at most `MAX_FUTURE_BLOCK_TIME` seconds ahead of the local clock.
+    /// - Ensures it meets the target expected at its height.
+    /// - Ensures it stays within `max_block_size` bytes and `max_block_transactions`.
+    /// - Ensures its Merkle root matches its transactions and no transaction appears twice, as
+    ///   repeating the last transactions can keep the Merkle root (see `merkle_root`).
+    /// - Ensures none of its transactions expired before its height.
+    /// - Ensures it starts with a single coinbase paid to its beneficiary, an address of the
+    ///   chain's network, that neither exceeds the block subsidy nor pushes the circulating
+    ///   supply above the total supply.
+    /// - Ensures the coinbase's nonce is the block height, so coinbases of different blocks
+    ///   have different ids, and that it carries no fee, signature or public key.
+    ///
+    /// Returns the circulating supply including the block's coinbase.
+    fn validate_block(
+        &self,
+        block: &Block,
+        previous_blocks: &[&Block],
+        circulating_supply: u64,
+    ) -> Result<u64, BlockValidationError> {
+        let previous_block: &Block = previous_blocks.last().unwrap();
+        let index = block.index;
+
+        if index != previous_block.index + 1 {
+            return Err(BlockValidationError::UnexpectedIndex {
+                expected: previous_block.index + 1,
+                found: index,
+            });
+        }
+        if block.previous_hash != previous_block.hash {
+            return Err(BlockValidationError::BrokenLink { index });
+        }
+        if block.hash != block.calculate_hash() {
+            return Err(BlockValidationError::InvalidHash { index });
+        }
+
+        // Verify the timestamp against the previous blocks and the local clock
+        let time = block
+            .time()
+            .ok_or(BlockValidationError::InvalidTimestamp { index })?;
+        let mut recent_times: Vec<_> = previous_blocks
+            [previous_blocks.len().saturating_sub(MEDIAN_TIME_SPAN)..]
+            .iter()
+            .filter_map(|block| block.time())
+            .collect();
+        recent_times.sort();
+        if let Some(median_time) = recent_times.get(recent_times.len() / 2) {
+            if time <= *median_time {
+                return Err(BlockValidationError::TimestampNotAfterMedian { index });
             }
+        }
+        if time > Utc::now() + TimeDelta::seconds(MAX_FUTURE_BLOCK_TIME) {
+            return Err(BlockValidationError::TimestampTooFarInFuture { index });
+        }
 
-            // Recalculate the current block's hash and validate it
-            let recalculated_hash: String = calculate_block_hash(
-                current_block.index,
-                &current_block.timestamp,
-                &current_block.transactions,
-                &current_block.previous_hash,
-                current_block.nonce,
-            );
-            if current_block.hash != recalculated_hash {
-                eprintln!("Block {} has an invalid hash!", current_block.index);
-                return false;
+        // Verify the block meets the target expected at its height
+        let expected_bits = self.expected_bits(previous_blocks);
+        if block.bits != expected_bits {
+            return Err(BlockValidationError::UnexpectedTarget {
+                index,
+                expected: expected_bits,
+                found: block.bits,
+            });
+        }
+        if !block.meets_target() {
+            return Err(BlockValidationError::TargetNotMet { index });
+        }
+
+        // Verify the block stays within the size limits
+        if block.transactions.len() > self.max_block_transactions {
+            return Err(BlockValidationError::TooManyTransactions {
+                index,
+                max: self.max_block_transactions,
+                found: block.transactions.len(),
+            });
+        }
+        let size = block.encode().len();
+        if size > self.max_block_size {
+            return Err(BlockValidationError::BlockTooLarge {
+                index,
+                max: self.max_block_size,
+                found: size,
+            });
+        }
+
+        // Verify the Merkle root commits to the block's transactions, each of them once
+        if block.merkle_root != calculate_merkle_root(&block.transactions) {
+            return Err(BlockValidationError::InvalidMerkleRoot { index });
+        }
+        let mut ids: HashSet<String> = HashSet::new();
+        for transaction in &block.transactions {
+            let id = transaction.id();
+            if ids.contains(&id) {
+                return Err(BlockValidationError::DuplicateTransaction { index, id });
             }
+            ids.insert(id);
+        }
+
+        // Verify no transaction expired before the block's height
+        for transaction in &block.transactions {
+            check_expiry(transaction, index)
+                .map_err(|error| BlockValidationError::InvalidTransaction { index, error })?;
+        }
+
+        // Verify the coinbase transaction
+        let coinbase: &Transaction = match block.transactions.first() {
+            Some(transaction) if transaction.is_coinbase() => transaction,
+            _ => return Err(BlockValidationError::MissingCoinbase { index }),
+        };
+        if block.transactions[1..]
+            .iter()
+            .any(|transaction| transaction.is_coinbase())
+        {
+            return Err(BlockValidationError::MultipleCoinbases { index });
+        }
+        if coinbase.receiver != block.beneficiary {
+            return Err(BlockValidationError::CoinbaseNotPaidToBeneficiary { index });
+        }
+        if coinbase.nonce != index as u64 {
+            return Err(BlockValidationError::CoinbaseNonceNotHeight {
+                index,
+                found: coinbase.nonce,
+            });
+        }
+        if coinbase.fee != 0 || coinbase.signature.is_some() || coinbase.public_key.is_some() {
+            return Err(BlockValidationError::MalformedCoinbase { index });
         }
+        if let Err(error) = Address::parse_for(&block.beneficiary, self.network) {
+            return Err(BlockValidationError::InvalidBeneficiary {
+                index,
+                address: block.beneficiary.clone(),
+                error,
+            });
+        }
+        let subsidy = self.block_subsidy(index);
+        if coinbase.amount > subsidy {
+            return Err(BlockValidationError::CoinbaseExceedsSubsidy {
+                index,
+                subsidy,
+                found: coinbase.amount,
+            });
+        }
+        match circulating_supply.checked_add(coinbase.amount) {
+            Some(supply) if supply <= self.token.total_supply => Ok(supply),
+            _ => Err(BlockValidationError::CoinbaseExceedsTotalSupply { index }),
+        }
+    }
 
-        true
+    /// Returns the transaction with the given id (see `Transaction::id`):
+    /// - From the main chain, with its location and the number of blocks from its block to
+    ///   the tip as confirmations.
+    /// - Otherwise from the mempool, without location and confirmations.
+    pub fn get_transaction(&self, id: &str) -> Option<IndexedTransaction> {
+        let Some(location) = self.transaction_index.get(id) else {
+            return self.mempool.get(id).map(|transaction| IndexedTransaction {
+                transaction: transaction.clone(),
+                location: None,
+                confirmations: 0,
+            });
+        };
+        let block = self.store.block_by_height(location.height)?;
+        let tip_height = self.store.len() as u32 - 1;
+        Some(IndexedTransaction {
+            transaction: block.transactions[location.position as usize].clone(),
+            location: Some(location),
+            confirmations: tip_height - location.height + 1,
+        })
     }
 
-    /// Returns the transaction history for the given address.
-    /// Scans all blocks in the chain and filters transactions involving the address.
-    pub fn get_transaction_history(&self, address: &String) -> Vec<Transaction> {
-        self.chain
+    /// Returns the transaction history for the given address, oldest first.
+    /// Looks the transactions up through the address index instead of scanning the chain.
+    pub fn get_transaction_history(&self, address: &str) -> Vec<Transaction> {
+        self.address_index
+            .entries(address)
             .iter()
-            .flat_map(|block| {
-                block
-                    .transactions
-                    .iter()
-                    .filter(|tx| tx.sender == *address || tx.receiver == *address)
-                    .cloned()
+            .filter_map(|entry| {
+                let block = self.store.block_by_height(entry.height)?;
+                block.transactions.get(entry.position as usize).cloned()
             })
             .collect()
     }
+
+    /// Returns a page of the history of the address from the address index, newest first,
+    /// with the direction of every transaction and the address's balance after it
+    /// (see `HistoryQuery`).
+    pub fn get_address_history(&self, address: &str, query: &HistoryQuery) -> Vec<&AddressEntry> {
+        self.address_index.history(address, query)
+    }
+}
+
+/// Ensures the transaction has not expired before the given block height.
+fn check_expiry(transaction: &Transaction, height: u32) -> Result<(), TransactionError> {
+    match transaction.valid_until {
+        Some(valid_until) if transaction.is_expired_at(height) => Err(TransactionError::Expired {
+            valid_until,
+            height,
+        }),
+        _ => Ok(()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::common::calculate_hash::calculate_block_hash;
-    use crate::test_utils::mock_config;
+    use crate::common::target::target_to_compact;
+    use crate::core::transaction_index::TransactionLocation;
+    use crate::errors::address_errors::AddressError;
+    use crate::test_utils::{address, miner, mock_config, signed_transaction, EASY_BITS};
+    use crate::wallet::wallet::Wallet;
+    use chrono::DateTime;
+    use tempfile::tempdir;
+
+    /// Recalculates the Merkle root and mines the hash of a tampered block again so only the
+    /// tampered content is invalid.
+    fn rehash(block: &mut Block) {
+        block.merkle_root = calculate_merkle_root(&block.transactions);
+        block.nonce = 0;
+        block.hash = block.calculate_hash();
+        while !block.meets_target() {
+            block.nonce += 1;
+            block.hash = block.calculate_hash();
+        }
+    }
+
+    /// Mines one block per transfer from a funded wallet and returns the bits of the chain.
+    fn mine_blocks(blockchain: &mut Blockchain, count: u64) -> Vec<u32> {
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 1_000);
+        for nonce in 0..count {
+            blockchain
+                .submit_transaction(signed_transaction(&alice, &address("Bob"), 1, 0, nonce))
+                .unwrap();
+            blockchain.add_block(&miner()).unwrap();
+        }
+        blockchain
+            .store
+            .blocks
+            .iter()
+            .map(|block| block.bits)
+            .collect()
+    }
+
+    /// Creates a block at the given offset in seconds from the Unix epoch.
+    /// The block is not mined for its bits; only its timestamp and bits are meaningful.
+    fn block_at(seconds: i64, bits: u32) -> Block {
+        let mut block = Block::new(0, vec![], "GENESIS".to_string(), miner(), EASY_BITS);
+        block.timestamp = DateTime::from_timestamp(seconds, 0).unwrap().to_rfc3339();
+        block.bits = bits;
+        block
+    }
+
+    /// Creates a blockchain whose genesis pre-mine funds the returned wallet, so its account
+    /// state survives replays.
+    fn blockchain_funding(config: &Config) -> (Blockchain, Wallet) {
+        let alice = Wallet::new();
+        let mut config = config.clone();
+        config.blockchain.genesis_miner = alice.get_address();
+        (Blockchain::new(config).unwrap(), alice)
+    }
+
+    /// Mines a valid block on top of the given parent, as a peer with another view of the
+    /// chain would.
+    fn mine_on(blockchain: &Blockchain, parent: &Block, transactions: Vec<Transaction>) -> Block {
+        let index = parent.index + 1;
+        let mut block_transactions = vec![Transaction::coinbase(
+            address("peer"),
+            blockchain.block_subsidy(index),
+            index,
+        )];
+        block_transactions.extend(transactions);
+        Block::new(
+            index,
+            block_transactions,
+            parent.hash.clone(),
+            address("peer"),
+            parent.bits,
+        )
+    }
+
+    /// Creates a blockchain with a funded wallet and mines one block.
+    fn blockchain_with_mined_block(config: Config) -> Blockchain {
+        let mut blockchain = Blockchain::new(config).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 0, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+        blockchain
+    }
     #[test]
     fn validate_genesis_block() {
         // Initialize the blockchain using the mock configuration
         let config = mock_config();
         let blockchain = Blockchain::new(config.clone()).unwrap();
 
-        let block = blockchain.chain.last().unwrap();
+        let block = blockchain.store.blocks.last().unwrap();
 
         assert_eq!(block.index, 0, "Genesis index should be 0");
 
@@ -263,12 +1320,12 @@ mod tests {
         let blockchain = Blockchain::new(config.clone()).unwrap();
 
         // Check that the mempool is empty at initialization
-        assert_eq!(blockchain.mempool, vec![], "Mempool should be empty");
+        assert!(blockchain.mempool.is_empty(), "Mempool should be empty");
 
-        // Check that the difficulty matches the configuration
+        // Check that the target matches the configuration
         assert_eq!(
-            blockchain.difficulty, config.blockchain.difficulty,
-            "Difficulty should be equal to config.blockchain.difficulty"
+            blockchain.genesis_bits, config.blockchain.genesis_bits,
+            "Genesis bits should be equal to config.blockchain.genesis_bits"
         );
 
         // Verify that the accounts are initialized with the genesis miner and pre-mined tokens
@@ -278,7 +1335,7 @@ mod tests {
             config.blockchain.genesis_pre_mined,
         );
         assert_eq!(
-            blockchain.accounts, accounts,
+            blockchain.store.balances, accounts,
             "Accounts should hold genesis_miner and genesis_pre_mined"
         );
 
@@ -293,7 +1350,7 @@ mod tests {
 
         // Check that the blockchain starts with only the genesis block
         assert_eq!(
-            blockchain.chain.len(),
+            blockchain.store.blocks.len(),
             1,
             "Blockchain should start with 1 block (genesis)"
         );
@@ -342,12 +1399,15 @@ mod tests {
         config.blockchain.genesis_pre_mined = config.token.total_supply + 1;
 
         // Attempt to initialize the blockchain with the invalid configuration
-        let blockchain_result: Result<Blockchain, String> = Blockchain::new(config.clone());
+        let blockchain_result = Blockchain::new(config.clone());
 
         // Assert that the initialization fails with the expected error
         assert_eq!(
             blockchain_result.unwrap_err(),
-            "ERR_TOTAL_SUPPLY_LESS_THAN_PRE_MINED",
+            BlockchainError::Config(ConfigError::TotalSupplyLessThanPreMined {
+                total_supply: config.token.total_supply,
+                pre_mined: config.token.total_supply + 1,
+            }),
             "The blockchain should return an error if the pre-mined tokens exceed the total supply"
         );
     }
@@ -356,48 +1416,53 @@ mod tests {
         // Initialize the blockchain with a mock configuration
         let config = mock_config();
         let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let test_a = Wallet::new();
 
         // Assert that the blockchain starts with only the genesis block
         assert_eq!(
-            blockchain.chain.len(),
+            blockchain.store.blocks.len(),
             1,
             "Blockchain should start with the genesis block"
         );
 
         // Add an account with a balance for testing
-        blockchain.accounts.insert("test_a".parse().unwrap(), 100);
+        blockchain.store.balances.insert(test_a.get_address(), 100);
 
         // Add a transaction to the mempool
-        blockchain.mempool.push(Transaction::new(
-            "test_a".parse().unwrap(),
-            "test_b".parse().unwrap(),
-            100,
-        ));
+        let transaction = signed_transaction(&test_a, &address("test_b"), 100, 0, 0);
+        blockchain.submit_transaction(transaction.clone()).unwrap();
 
         // Add a new block to the blockchain
-        blockchain.add_block();
+        blockchain.add_block(&miner()).unwrap();
 
         // Get the last block in the chain for validation
-        let block = blockchain.chain.last().unwrap();
+        let block = blockchain.store.blocks.last().unwrap();
 
         // Assert that the blockchain now contains two blocks (genesis + the new block)
-        assert_eq!(blockchain.chain.len(), 2, "Blockchain should have 2 blocks");
+        assert_eq!(
+            blockchain.store.blocks.len(),
+            2,
+            "Blockchain should have 2 blocks"
+        );
 
-        // Assert that the last block contains exactly one transaction
+        // Assert that the last block contains the coinbase and exactly one transaction
         assert_eq!(
             block.transactions.len(),
-            1,
-            "The last block should contain one transaction"
+            2,
+            "The last block should contain the coinbase and one transaction"
+        );
+
+        // Assert that the coinbase pays the block subsidy to the beneficiary
+        assert_eq!(
+            block.transactions[0],
+            Transaction::coinbase(miner(), config.blockchain.initial_subsidy, 1),
+            "The first transaction should be the coinbase"
         );
 
         // Assert that the transaction in the block matches the one added to the mempool
         assert_eq!(
-            block.transactions,
-            vec![Transaction::new(
-                "test_a".parse().unwrap(),
-                "test_b".parse().unwrap(),
-                100
-            )],
+            block.transactions[1..],
+            [transaction],
             "Transactions should be added to the last block"
         );
 
@@ -407,9 +1472,11 @@ mod tests {
             calculate_block_hash(
                 1,
                 &block.timestamp,
-                &block.transactions,
+                &block.merkle_root,
                 &block.previous_hash,
-                block.nonce,
+                &block.beneficiary,
+                block.bits,
+                block.nonce
             ),
             "Hash of the last block should match the calculated hash"
         );
@@ -422,7 +1489,7 @@ mod tests {
 
         // Assert that the mempool is empty after transactions are added to the new block
         assert_eq!(
-            blockchain.mempool,
+            blockchain.mempool.transactions(),
             vec![],
             "Mempool should be empty after adding a block"
         );
@@ -432,18 +1499,17 @@ mod tests {
         // Initialize the blockchain with a mock configuration
         let config = mock_config();
         let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let test_a = Wallet::new();
 
         // Add a valid block
-        blockchain.accounts.insert("test_a".parse().unwrap(), 100);
-        blockchain.mempool.push(Transaction::new(
-            "test_a".parse().unwrap(),
-            "test_b".parse().unwrap(),
-            50,
-        ));
-        blockchain.add_block();
+        blockchain.store.balances.insert(test_a.get_address(), 100);
+        blockchain
+            .submit_transaction(signed_transaction(&test_a, &address("test_b"), 50, 0, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
 
         // Temper the second block
-        let block = blockchain.chain.last_mut().unwrap();
+        let block = blockchain.store.blocks.last_mut().unwrap();
         block.timestamp = "2025-01-01T00:00:00Z".to_string();
 
         assert!(
@@ -452,26 +1518,40 @@ mod tests {
         );
     }
     #[test]
-    fn previous_hash_mismatch_invalidates_chain() {
-        // Initialize the blockchain with a mock configuration
+    fn tampered_transaction_invalidates_chain() {
         let config = mock_config();
-        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let mut blockchain = blockchain_with_mined_block(config);
+
+        // Change a transaction without updating the Merkle root
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.transactions[1].receiver = "Mallory".to_string();
+
+        assert!(
+            !blockchain.is_valid(),
+            "The blockchain should be invalid when a transaction no longer matches the Merkle root"
+        );
+    }
+    #[test]
+    fn previous_hash_mismatch_invalidates_chain() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let test_a = Wallet::new();
 
         // Add a valid block
-        blockchain.accounts.insert("test_a".parse().unwrap(), 100);
-        blockchain.mempool.push(Transaction::new(
-            "test_a".parse().unwrap(),
-            "test_b".parse().unwrap(),
-            50,
-        ));
-        blockchain.add_block();
+        blockchain.store.balances.insert(test_a.get_address(), 100);
+        blockchain
+            .submit_transaction(signed_transaction(&test_a, &address("test_b"), 50, 0, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
 
         // Change the previous_hash
-        let block = blockchain.chain.last_mut().unwrap();
+        let block = blockchain.store.blocks.last_mut().unwrap();
         block.previous_hash = "123456789".to_string();
 
-        assert!(
-            !blockchain.is_valid(),
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::BrokenLink { index: 1 }),
             "The blockchain should be invalid when previous hash does not match"
         );
     }
@@ -480,34 +1560,36 @@ mod tests {
         // Initialize the blockchain with a mock configuration
         let config = mock_config();
         let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let test_a = Wallet::new();
 
         // Add a valid block
-        blockchain.accounts.insert("test_a".to_string(), 100);
-        blockchain.mempool.push(Transaction::new(
-            "test_a".to_string(),
-            "test_b".to_string(),
-            50,
-        ));
-        blockchain.add_block();
+        blockchain.store.balances.insert(test_a.get_address(), 100);
+        blockchain
+            .submit_transaction(signed_transaction(&test_a, &address("test_b"), 50, 0, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
 
         // Tamper with the blockchain to make it invalid
-        let last_block = blockchain.chain.last_mut().unwrap();
+        let last_block = blockchain.store.blocks.last_mut().unwrap();
         last_block.previous_hash = "invalid_previous_hash".to_string();
 
         // Save the chain length before attempting to add the block
-        let chain_length_before = blockchain.chain.len();
+        let chain_length_before = blockchain.store.blocks.len();
 
         // Add another block (this should trigger a rollback)
-        blockchain.mempool.push(Transaction::new(
-            "test_a".to_string(),
-            "test_b".to_string(),
-            50,
-        ));
-        blockchain.add_block();
+        blockchain
+            .submit_transaction(signed_transaction(&test_a, &address("test_b"), 50, 0, 1))
+            .unwrap();
+        assert_eq!(
+            blockchain.add_block(&miner()),
+            Err(BlockchainError::BlockValidation(
+                BlockValidationError::BrokenLink { index: 1 }
+            ))
+        );
 
         // Assert: Verify the chain length has not increased
         assert_eq!(
-            blockchain.chain.len(),
+            blockchain.store.blocks.len(),
             chain_length_before,
             "Blockchain length should not increase after adding an invalid block"
         );
@@ -525,14 +1607,16 @@ mod tests {
         let blockchain = Blockchain::new(config.clone()).unwrap();
 
         let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
         temp_balances.insert("Alice".to_string(), 100);
 
         // Sender is empty
-        let transaction = Transaction::new("".to_string(), "Bob".to_string(), 50);
+        let transaction = Transaction::new("".to_string(), "Bob".to_string(), 50, 0, 0);
         let result = Blockchain::validate_transaction_with_temp_balances(
             &blockchain,
             &transaction,
             &mut temp_balances,
+            &mut temp_nonces,
         );
         assert_eq!(
             result,
@@ -541,11 +1625,12 @@ mod tests {
         );
 
         // Receiver is empty
-        let transaction = Transaction::new("Alice".to_string(), "".to_string(), 50);
+        let transaction = Transaction::new("Alice".to_string(), "".to_string(), 50, 0, 0);
         let result = Blockchain::validate_transaction_with_temp_balances(
             &blockchain,
             &transaction,
             &mut temp_balances,
+            &mut temp_nonces,
         );
         assert_eq!(
             result,
@@ -560,14 +1645,16 @@ mod tests {
         let blockchain = Blockchain::new(config.clone()).unwrap();
 
         let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
         temp_balances.insert("Alice".to_string(), 100);
 
         // Sender and receiver are the same
-        let transaction = Transaction::new("Alice".to_string(), "Alice".to_string(), 50);
+        let transaction = Transaction::new("Alice".to_string(), "Alice".to_string(), 50, 0, 0);
         let result = Blockchain::validate_transaction_with_temp_balances(
             &blockchain,
             &transaction,
             &mut temp_balances,
+            &mut temp_nonces,
         );
         assert_eq!(
             result,
@@ -582,14 +1669,16 @@ mod tests {
         let blockchain = Blockchain::new(config.clone()).unwrap();
 
         let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
         temp_balances.insert("Alice".to_string(), 100);
 
         // Amount is zero
-        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 0);
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 0, 0, 0);
         let result = Blockchain::validate_transaction_with_temp_balances(
             &blockchain,
             &transaction,
             &mut temp_balances,
+            &mut temp_nonces,
         );
         assert_eq!(
             result,
@@ -598,25 +1687,177 @@ mod tests {
         );
     }
     #[test]
+    fn reject_transaction_without_public_key_or_signature() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+
+        let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
+        temp_balances.insert(alice.get_address(), 100);
+
+        // Unsigned transaction carries no public key
+        let transaction = Transaction::new(alice.get_address(), address("Bob"), 50, 0, 0);
+        let result = Blockchain::validate_transaction_with_temp_balances(
+            &blockchain,
+            &transaction,
+            &mut temp_balances,
+            &mut temp_nonces,
+        );
+        assert_eq!(
+            result,
+            Err(TransactionError::MissingPublicKey),
+            "Transaction without public key should fail"
+        );
+
+        // Public key present but signature stripped
+        let mut transaction = signed_transaction(&alice, &address("Bob"), 50, 0, 0);
+        transaction.signature = None;
+        let result = Blockchain::validate_transaction_with_temp_balances(
+            &blockchain,
+            &transaction,
+            &mut temp_balances,
+            &mut temp_nonces,
+        );
+        assert_eq!(
+            result,
+            Err(TransactionError::MissingSignature),
+            "Transaction without signature should fail"
+        );
+    }
+    #[test]
+    fn reject_transaction_with_public_key_of_another_address() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        let mallory = Wallet::new();
+
+        let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
+        temp_balances.insert(alice.get_address(), 100);
+
+        // Mallory signs a transaction spending from Alice's address
+        let mut transaction = Transaction::new(alice.get_address(), address("Bob"), 50, 0, 0);
+        let signature = mallory.sign_transaction(&transaction.signing_payload());
+        transaction.sign(signature, mallory.public_key);
+
+        let result = Blockchain::validate_transaction_with_temp_balances(
+            &blockchain,
+            &transaction,
+            &mut temp_balances,
+            &mut temp_nonces,
+        );
+        assert_eq!(
+            result,
+            Err(TransactionError::PublicKeyDoesNotMatchSender {
+                sender: alice.get_address(),
+            }),
+            "Transaction with a public key not belonging to the sender should fail"
+        );
+    }
+    #[test]
+    fn reject_transaction_to_malformed_or_foreign_address() {
+        // Arrange
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+        let bob = address("Bob");
+        let typo = if &bob[10..11] == "x" { "y" } else { "x" };
+        let mistyped = format!("{}{}{}", &bob[..10], typo, &bob[11..]);
+        let testnet = Wallet::new().with_network(Network::Testnet).get_address();
+
+        // Act & Assert
+        let rejection = |blockchain: &mut Blockchain, receiver: &str| match blockchain
+            .submit_transaction(signed_transaction(&alice, receiver, 10, 0, 0))
+        {
+            Err(TransactionError::InvalidAddress { address, error }) => {
+                assert_eq!(address, receiver);
+                Some(error)
+            }
+            _ => None,
+        };
+        assert_eq!(
+            rejection(&mut blockchain, &mistyped),
+            Some(AddressError::InvalidChecksum),
+            "A mistyped receiver should fail the checksum"
+        );
+        assert_eq!(
+            rejection(&mut blockchain, &testnet),
+            Some(AddressError::WrongNetwork {
+                expected: "tok".to_string(),
+                found: "ttok".to_string()
+            })
+        );
+        assert!(matches!(
+            rejection(&mut blockchain, "Bob"),
+            Some(AddressError::Malformed { .. })
+        ));
+        assert!(
+            blockchain.store.balance(&mistyped).is_none(),
+            "No account should be created for a mistyped address"
+        );
+        assert_eq!(
+            blockchain.submit_transaction(signed_transaction(&alice, &bob, 10, 0, 0)),
+            Ok(())
+        );
+    }
+    #[test]
+    fn reject_transaction_with_invalid_signature() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+
+        let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
+        temp_balances.insert(alice.get_address(), 100);
+
+        // Amount is changed after signing
+        let mut transaction = signed_transaction(&alice, &address("Bob"), 50, 0, 0);
+        transaction.amount = 100;
+
+        let result = Blockchain::validate_transaction_with_temp_balances(
+            &blockchain,
+            &transaction,
+            &mut temp_balances,
+            &mut temp_nonces,
+        );
+        assert_eq!(
+            result,
+            Err(TransactionError::InvalidSignature),
+            "Transaction with a signature that does not verify should fail"
+        );
+        assert_eq!(
+            temp_balances[&alice.get_address()],
+            100,
+            "Sender's balance should not change for a rejected transaction"
+        );
+    }
+    #[test]
     fn reject_transaction_with_insufficient_balance() {
         // Initialize the blockchain with a mock configuration
         let config = mock_config();
         let blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
 
         let mut temp_balances = HashMap::new();
-        temp_balances.insert("Alice".to_string(), 50);
+        let mut temp_nonces = HashMap::new();
+        temp_balances.insert(alice.get_address(), 50);
 
         // Amount exceeds sender's balance
-        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 100);
+        let transaction = signed_transaction(&alice, &address("Bob"), 100, 0, 0);
         let result = Blockchain::validate_transaction_with_temp_balances(
             &blockchain,
             &transaction,
             &mut temp_balances,
+            &mut temp_nonces,
         );
         assert_eq!(
             result,
             Err(TransactionError::InsufficientBalance {
-                sender: "Alice".to_string(),
+                sender: alice.get_address(),
                 requested: 100,
                 available: 50,
             }),
@@ -628,20 +1869,23 @@ mod tests {
         // Initialize the blockchain with a mock configuration
         let config = mock_config();
         let blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
 
         let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
 
         // Sender does not exist in temp_balances
-        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 50);
+        let transaction = signed_transaction(&alice, &address("Bob"), 50, 0, 0);
         let result = Blockchain::validate_transaction_with_temp_balances(
             &blockchain,
             &transaction,
             &mut temp_balances,
+            &mut temp_nonces,
         );
         assert_eq!(
             result,
             Err(TransactionError::SenderDoesNotExist {
-                sender: "Alice".to_string(),
+                sender: alice.get_address(),
             }),
             "Transaction with sender not in temp_balances should fail"
         );
@@ -651,303 +1895,1215 @@ mod tests {
         // Initialize the blockchain with a mock configuration
         let config = mock_config();
         let blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
 
         let mut temp_balances = HashMap::new();
-        temp_balances.insert("Alice".to_string(), 100);
-        temp_balances.insert("Bob".to_string(), 0);
+        let mut temp_nonces = HashMap::new();
+        temp_balances.insert(alice.get_address(), 100);
+        temp_balances.insert(address("Bob"), 0);
 
         // Valid transaction
-        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 50);
+        let transaction = signed_transaction(&alice, &address("Bob"), 50, 0, 0);
         let result = Blockchain::validate_transaction_with_temp_balances(
             &blockchain,
             &transaction,
             &mut temp_balances,
+            &mut temp_nonces,
         );
         assert!(result.is_ok(), "Valid transaction should succeed");
 
         // Check updated balances
         assert_eq!(
-            temp_balances["Alice"], 50,
+            temp_balances[&alice.get_address()],
+            50,
             "Sender's balance should be updated"
         );
         assert_eq!(
-            temp_balances["Bob"], 50,
+            temp_balances[&address("Bob")],
+            50,
             "Receiver's balance should be updated"
         );
+        assert_eq!(
+            temp_nonces[&alice.get_address()],
+            1,
+            "Sender's nonce should be incremented"
+        );
     }
     #[test]
-    fn process_mempool_filters_invalid_transactions() {
+    fn reject_transaction_with_invalid_nonce() {
         // Initialize the blockchain with a mock configuration
         let config = mock_config();
-        let mut blockchain = Blockchain::new(config.clone()).unwrap();
-
-        // Add some initial accounts and balances
-        blockchain.accounts.insert("Alice".to_string(), 100);
-        blockchain.accounts.insert("Bob".to_string(), 50);
-
-        // Add valid and invalid transactions to the mempool
-        blockchain
-            .mempool
-            .push(Transaction::new("Alice".to_string(), "Bob".to_string(), 50)); // Valid
-        blockchain.mempool.push(Transaction::new(
-            "Alice".to_string(),
-            "Bob".to_string(),
-            200,
-        )); // Invalid: Insufficient balance
-        blockchain.mempool.push(Transaction::new(
-            "Alice".to_string(),
-            "Alice".to_string(),
-            50,
-        )); // Invalid: Sender and receiver are the same
-        blockchain.mempool.push(Transaction::new(
-            "Unknown".to_string(),
-            "Bob".to_string(),
-            50,
-        )); // Invalid: Sender does not exist
+        let blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
 
-        // Act: Process the mempool
-        let valid_transactions = blockchain.process_mempool();
+        let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
+        temp_balances.insert(alice.get_address(), 100);
+        temp_nonces.insert(alice.get_address(), 1);
 
-        // Assert: Only valid transactions should be processed
-        assert_eq!(
-            valid_transactions.len(),
-            1,
-            "Only one valid transaction should be processed"
+        // Nonce already used
+        let transaction = signed_transaction(&alice, &address("Bob"), 50, 0, 0);
+        let result = Blockchain::validate_transaction_with_temp_balances(
+            &blockchain,
+            &transaction,
+            &mut temp_balances,
+            &mut temp_nonces,
         );
         assert_eq!(
-            valid_transactions[0],
-            Transaction::new("Alice".to_string(), "Bob".to_string(), 50),
-            "The valid transaction should match the expected transaction"
+            result,
+            Err(TransactionError::InvalidNonce {
+                sender: alice.get_address(),
+                expected: 1,
+                found: 0,
+            }),
+            "Transaction with an already used nonce should fail"
         );
 
-        // Assert: The mempool should be cleared
-        assert_eq!(
-            blockchain.mempool.len(),
-            0,
-            "The mempool should be cleared after processing"
+        // Nonce skips ahead
+        let transaction = signed_transaction(&alice, &address("Bob"), 50, 0, 3);
+        let result = Blockchain::validate_transaction_with_temp_balances(
+            &blockchain,
+            &transaction,
+            &mut temp_balances,
+            &mut temp_nonces,
         );
-    }
-    #[test]
-    fn retrieve_transaction_history() {
-        // Initialize the blockchain using the mock configuration
-        let config = mock_config();
-        let mut blockchain = Blockchain::new(config.clone()).unwrap();
-
-        // Add accounts and balances
-        blockchain.accounts.insert("Alice".to_string(), 200);
-        blockchain.accounts.insert("Bob".to_string(), 100);
-        blockchain.accounts.insert("Charlie".to_string(), 300);
-
-        // Add a few transactions
-        blockchain
-            .mempool
-            .push(Transaction::new("Alice".to_string(), "Bob".to_string(), 50));
-        blockchain.add_block(); // Block 1
-
-        blockchain.mempool.push(Transaction::new(
-            "Bob".to_string(),
-            "Charlie".to_string(),
-            30,
-        ));
-        blockchain.mempool.push(Transaction::new(
-            "Alice".to_string(),
-            "Charlie".to_string(),
-            70,
-        ));
-        blockchain.add_block(); // Block 2
-
-        blockchain.mempool.push(Transaction::new(
-            "Charlie".to_string(),
-            "Alice".to_string(),
-            20,
-        ));
-        blockchain.add_block(); // Block 3
-
-        // Get the transaction history for each address
-        let alice_history = blockchain.get_transaction_history(&"Alice".to_string());
-        let bob_history = blockchain.get_transaction_history(&"Bob".to_string());
-        let charlie_history = blockchain.get_transaction_history(&"Charlie".to_string());
-
-        // Verify Alice's transaction history
         assert_eq!(
-            alice_history,
-            vec![
-                Transaction::new("Alice".to_string(), "Bob".to_string(), 50),
-                Transaction::new("Alice".to_string(), "Charlie".to_string(), 70),
-                Transaction::new("Charlie".to_string(), "Alice".to_string(), 20)
-            ],
-            "Alice's transaction history should include all transactions involving her as sender or receiver"
+            result,
+            Err(TransactionError::InvalidNonce {
+                sender: alice.get_address(),
+                expected: 1,
+                found: 3,
+            }),
+            "Transaction with a nonce gap should fail"
         );
 
-        // Verify Bob's transaction history
+        // Nothing changed for the rejected transactions
         assert_eq!(
-            bob_history,
-            vec![
-                Transaction::new("Alice".to_string(), "Bob".to_string(), 50),
-                Transaction::new("Bob".to_string(), "Charlie".to_string(), 30)
-            ],
-            "Bob's transaction history should include all transactions involving him as sender or receiver"
+            temp_balances[&alice.get_address()],
+            100,
+            "Sender's balance should not change for rejected transactions"
         );
-
-        // Verify Charlie's transaction history
         assert_eq!(
-            charlie_history,
-            vec![
-                Transaction::new("Bob".to_string(), "Charlie".to_string(), 30),
-                Transaction::new("Alice".to_string(), "Charlie".to_string(), 70),
-                Transaction::new("Charlie".to_string(), "Alice".to_string(), 20)
-            ],
-            "Charlie's transaction history should include all transactions involving him as sender or receiver"
+            temp_nonces[&alice.get_address()],
+            1,
+            "Sender's nonce should not change for rejected transactions"
         );
     }
     #[test]
-    fn block_is_not_created_without_transactions() {
-        // Initialize the blockchain using the mock configuration
-        let config = mock_config();
-        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+    fn reject_transaction_with_nonce_far_ahead() {
+        // Arrange
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+
+        // Act
+        let result = blockchain.submit_transaction(signed_transaction(
+            &alice,
+            &address("Bob"),
+            10,
+            0,
+            u64::MAX,
+        ));
 
+        // Assert
         assert_eq!(
-            blockchain.chain.len(),
-            1,
-            "Blockchain should start with the genesis block"
+            result,
+            Err(TransactionError::NonceTooFarAhead {
+                sender: alice.get_address(),
+                next: 0,
+                found: u64::MAX,
+            }),
+            "A nonce no pending transactions could reach should be rejected"
         );
+        assert!(blockchain.mempool.is_empty());
 
-        blockchain.add_block();
-
+        // The last nonce cannot be followed by another one
+        let mut temp_balances = HashMap::from([(alice.get_address(), 100)]);
+        let mut temp_nonces = HashMap::from([(alice.get_address(), u64::MAX)]);
+        let transaction = signed_transaction(&alice, &address("Bob"), 10, 0, u64::MAX);
         assert_eq!(
-            blockchain.chain.len(),
-            1,
-            "No new block should be created when there are no transactions"
+            blockchain.validate_transaction_with_temp_balances(
+                &transaction,
+                &mut temp_balances,
+                &mut temp_nonces,
+            ),
+            Err(TransactionError::InvalidNonce {
+                sender: alice.get_address(),
+                expected: u64::MAX,
+                found: u64::MAX,
+            }),
+            "Overflowing the nonce should be rejected"
         );
+        assert_eq!(temp_balances[&alice.get_address()], 100);
     }
     #[test]
-    fn add_multiple_blocks_and_verify_chain_integrity_and_balances() {
-        // Initialize the blockchain using the mock configuration
+    fn replayed_transaction_is_not_mined_twice() {
+        // Initialize the blockchain with a mock configuration
         let config = mock_config();
         let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
 
-        // Set initial balances
-        blockchain.accounts.insert("Alice".to_string(), 300);
-        blockchain.accounts.insert("Bob".to_string(), 0);
-        blockchain.accounts.insert("Charlie".to_string(), 0);
-
-        // Add transactions to the mempool and add the first block
-        blockchain.mempool.push(Transaction::new(
-            "Alice".to_string(),
-            "Bob".to_string(),
-            100,
-        ));
-        blockchain.add_block();
-
-        // Add more transactions to the mempool and add the second block
-        blockchain.mempool.push(Transaction::new(
-            "Bob".to_string(),
-            "Charlie".to_string(),
-            50,
-        ));
-        blockchain.mempool.push(Transaction::new(
-            "Alice".to_string(),
-            "Charlie".to_string(),
-            50,
-        ));
-        blockchain.add_block();
-
-        // Check the chain length
+        // The same transaction is submitted twice before mining
+        let transaction = signed_transaction(&alice, &address("Bob"), 10, 0, 0);
+        blockchain.submit_transaction(transaction.clone()).unwrap();
         assert_eq!(
-            blockchain.chain.len(),
-            3,
-            "Blockchain should contain 3 blocks (genesis + 2 new blocks)"
-        );
-
-        // Validate the chain integrity
-        assert!(
-            blockchain.is_valid(),
-            "Blockchain should be valid after adding multiple blocks"
+            blockchain.submit_transaction(transaction.clone()),
+            Err(TransactionError::AlreadyInMempool)
         );
+        blockchain.add_block(&miner()).unwrap();
 
-        // Check account balances
-        assert_eq!(
-            blockchain.accounts.get("Alice").unwrap(),
-            &150,
-            "Alice's balance should be updated correctly"
-        );
-        assert_eq!(
-            blockchain.accounts.get("Bob").unwrap(),
-            &50,
-            "Bob's balance should be updated correctly"
-        );
         assert_eq!(
-            blockchain.accounts.get("Charlie").unwrap(),
-            &100,
-            "Charlie's balance should be updated correctly"
+            blockchain.store.blocks.last().unwrap().transactions[1..].to_vec(),
+            vec![transaction.clone()],
+            "Only one copy of a duplicated transaction should be mined"
         );
 
-        // Verify transactions in blocks
-        let block_1 = &blockchain.chain[1];
+        // The mined transaction is replayed after mining
         assert_eq!(
-            block_1.transactions.len(),
-            1,
-            "Block 1 should contain 1 transaction"
+            blockchain.submit_transaction(transaction),
+            Err(TransactionError::InvalidNonce {
+                sender: alice.get_address(),
+                expected: 1,
+                found: 0,
+            }),
+            "Replayed transactions should not be admitted"
         );
         assert_eq!(
-            block_1.transactions[0],
-            Transaction::new("Alice".to_string(), "Bob".to_string(), 100),
-            "Block 1 transaction should match the expected transaction"
+            blockchain.add_block(&miner()),
+            Err(BlockchainError::NoValidTransactions)
         );
 
-        let block_2 = &blockchain.chain[2];
         assert_eq!(
-            block_2.transactions.len(),
+            blockchain.store.blocks.len(),
             2,
-            "Block 2 should contain 2 transactions"
+            "A replayed transaction should not produce a new block"
         );
         assert_eq!(
-            block_2.transactions[0],
-            Transaction::new("Bob".to_string(), "Charlie".to_string(), 50),
-            "Block 2 first transaction should match the expected transaction"
+            blockchain.store.balances[&alice.get_address()],
+            90,
+            "The sender should only be debited once"
         );
         assert_eq!(
-            block_2.transactions[1],
-            Transaction::new("Alice".to_string(), "Charlie".to_string(), 50),
-            "Block 2 second transaction should match the expected transaction"
+            blockchain.store.nonces[&alice.get_address()],
+            1,
+            "The sender's nonce should only be incremented once"
+        );
+        assert_eq!(
+            blockchain.mempool.len(),
+            0,
+            "Replayed transactions should be removed from the mempool"
         );
     }
     #[test]
-    fn transaction_that_causes_balance_overflow_is_rejected() {
-        // Initialize the blockchain using the mock configuration
+    fn transaction_with_nonce_gap_is_held_until_executable() {
+        // Initialize the blockchain with a mock configuration
         let config = mock_config();
         let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
 
-        // Set up an account with a balance near u64::MAX
-        let near_max_balance = u64::MAX - 10;
-        blockchain.accounts.insert("Alice".to_string(), near_max_balance);
-        blockchain.accounts.insert("Bob".to_string(), 11);
-
-        // Add a transaction that would cause Alice's balance to overflow
-        blockchain.mempool.push(Transaction::new(
-            "Bob".to_string(),
-            "Alice".to_string(),
-            11,
-        ));
-
-        // Attempt to add a block
-        blockchain.add_block();
+        // Only the second transaction of Alice arrives
+        let second = signed_transaction(&alice, &address("Bob"), 20, 0, 1);
+        blockchain.submit_transaction(second.clone()).unwrap();
+        assert_eq!(
+            blockchain.add_block(&miner()),
+            Err(BlockchainError::NoValidTransactions)
+        );
 
-        // Ensure the blockchain length remains 1 (only the genesis block)
         assert_eq!(
-            blockchain.chain.len(),
+            blockchain.store.blocks.len(),
             1,
-            "Blockchain should not add a block with an overflowing transaction"
+            "No block should be created while the nonce gap exists"
         );
-
-        // Ensure balances remain unchanged
         assert_eq!(
-            blockchain.accounts.get("Alice").unwrap(),
+            blockchain.mempool.transactions(),
+            vec![second.clone()],
+            "The transaction should be held in the mempool"
+        );
+
+        // The missing transaction arrives
+        let first = signed_transaction(&alice, &address("Bob"), 10, 0, 0);
+        blockchain.submit_transaction(first.clone()).unwrap();
+        blockchain.add_block(&miner()).unwrap();
+
+        assert_eq!(
+            blockchain.store.blocks.last().unwrap().transactions[1..],
+            [first, second],
+            "Both transactions should be mined in nonce order"
+        );
+        assert_eq!(
+            blockchain.store.balances[&alice.get_address()],
+            70,
+            "Both transactions should be executed"
+        );
+        assert_eq!(
+            blockchain.store.nonces[&alice.get_address()],
+            2,
+            "The sender's nonce should be advanced past both transactions"
+        );
+        assert_eq!(
+            blockchain.mempool.len(),
+            0,
+            "The mempool should be empty once the held transaction is mined"
+        );
+    }
+    #[test]
+    fn reject_transaction_when_fee_exceeds_remaining_balance() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+
+        let mut temp_balances = HashMap::new();
+        let mut temp_nonces = HashMap::new();
+        temp_balances.insert(alice.get_address(), 100);
+
+        // Amount alone is affordable, amount plus fee is not
+        let transaction = signed_transaction(&alice, &address("Bob"), 100, 1, 0);
+        let result = Blockchain::validate_transaction_with_temp_balances(
+            &blockchain,
+            &transaction,
+            &mut temp_balances,
+            &mut temp_nonces,
+        );
+        assert_eq!(
+            result,
+            Err(TransactionError::InsufficientBalance {
+                sender: alice.get_address(),
+                requested: 101,
+                available: 100,
+            }),
+            "Transaction whose amount plus fee exceeds the balance should fail"
+        );
+    }
+    #[test]
+    fn fees_are_paid_by_sender_and_credited_to_beneficiary() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+        blockchain.store.balances.insert(bob.get_address(), 100);
+
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Charlie"), 50, 3, 0))
+            .unwrap();
+        blockchain
+            .submit_transaction(signed_transaction(&bob, &address("Charlie"), 20, 2, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+
+        let block = blockchain.store.blocks.last().unwrap();
+        assert_eq!(
+            block.beneficiary,
+            miner(),
+            "The block should record the beneficiary"
+        );
+        assert_eq!(block.total_fees(), 5, "The block should hold both fees");
+        assert_eq!(
+            blockchain.store.balances[&alice.get_address()],
+            47,
+            "Alice should pay the amount plus fee"
+        );
+        assert_eq!(
+            blockchain.store.balances[&bob.get_address()],
+            78,
+            "Bob should pay the amount plus fee"
+        );
+        assert_eq!(
+            blockchain.store.balances[&address("Charlie")],
+            70,
+            "The receiver should only get the amounts"
+        );
+        assert_eq!(
+            blockchain.store.balances[&miner()],
+            config.blockchain.initial_subsidy + 5,
+            "The beneficiary should collect the subsidy and all fees"
+        );
+    }
+    #[test]
+    fn process_mempool_selects_highest_fee_first() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        let charlie = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+        blockchain.store.balances.insert(bob.get_address(), 100);
+        blockchain.store.balances.insert(charlie.get_address(), 100);
+
+        let cheap = signed_transaction(&alice, &address("Dave"), 10, 1, 0);
+        let expensive = signed_transaction(&bob, &address("Dave"), 10, 9, 0);
+        let average = signed_transaction(&charlie, &address("Dave"), 10, 5, 0);
+        blockchain.submit_transaction(cheap.clone()).unwrap();
+        blockchain.submit_transaction(expensive.clone()).unwrap();
+        blockchain.submit_transaction(average.clone()).unwrap();
+
+        let valid_transactions = blockchain.process_mempool(usize::MAX);
+
+        assert_eq!(
+            valid_transactions,
+            vec![expensive, average, cheap],
+            "Transactions should be selected by descending fee"
+        );
+    }
+    #[test]
+    fn high_fee_transaction_is_selected_after_its_lower_nonce() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+        blockchain.store.balances.insert(bob.get_address(), 100);
+
+        // Alice's second transaction pays more than her first one
+        let first = signed_transaction(&alice, &address("Charlie"), 10, 1, 0);
+        let second = signed_transaction(&alice, &address("Charlie"), 10, 10, 1);
+        let other = signed_transaction(&bob, &address("Charlie"), 10, 5, 0);
+        blockchain.submit_transaction(second.clone()).unwrap();
+        blockchain.submit_transaction(first.clone()).unwrap();
+        blockchain.submit_transaction(other.clone()).unwrap();
+
+        let valid_transactions = blockchain.process_mempool(usize::MAX);
+
+        assert_eq!(
+            valid_transactions,
+            vec![other, first, second],
+            "The dependent transaction should be selected once its predecessor is"
+        );
+        assert_eq!(
+            blockchain.mempool.len(),
+            3,
+            "Selected transactions should stay in the mempool until their block is added"
+        );
+    }
+    #[test]
+    fn process_mempool_filters_invalid_transactions() {
+        // Initialize the blockchain with a mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        let unknown = Wallet::new();
+
+        // Add some initial accounts and balances
+        blockchain.store.balances.insert(alice.get_address(), 100);
+        blockchain.store.balances.insert(bob.get_address(), 50);
+
+        // Add valid and invalid transactions to the mempool, bypassing admission
+        let valid_transaction = signed_transaction(&alice, &bob.get_address(), 50, 0, 0);
+        blockchain
+            .mempool
+            .insert(valid_transaction.clone())
+            .unwrap(); // Valid
+        blockchain
+            .mempool
+            .insert(signed_transaction(&alice, &bob.get_address(), 200, 0, 1))
+            .unwrap(); // Invalid: Insufficient balance
+        blockchain
+            .mempool
+            .insert(signed_transaction(&alice, &alice.get_address(), 50, 0, 1))
+            .unwrap(); // Invalid: Sender and receiver are the same
+        blockchain
+            .mempool
+            .insert(signed_transaction(&unknown, &bob.get_address(), 50, 0, 0))
+            .unwrap(); // Invalid: Sender does not exist
+        blockchain
+            .mempool
+            .insert(Transaction::new(
+                bob.get_address(),
+                alice.get_address(),
+                10,
+                0,
+                0,
+            ))
+            .unwrap(); // Invalid: Not signed
+
+        // Act: Process the mempool
+        let valid_transactions = blockchain.process_mempool(usize::MAX);
+
+        // Assert: Only valid transactions should be processed
+        assert_eq!(
+            valid_transactions.len(),
+            1,
+            "Only one valid transaction should be processed"
+        );
+        assert_eq!(
+            valid_transactions[0], valid_transaction,
+            "The valid transaction should match the expected transaction"
+        );
+
+        // Assert: Only the valid transaction should be left in the mempool
+        assert_eq!(
+            blockchain.mempool.transactions(),
+            &[valid_transaction],
+            "Invalid transactions should be removed from the mempool"
+        );
+    }
+    #[test]
+    fn submit_transaction_reports_why_it_is_rejected() {
+        // Arrange
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        let alice = Wallet::new();
+        let unknown = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+
+        // Act & Assert
+        assert_eq!(
+            blockchain.submit_transaction(signed_transaction(&unknown, &address("Bob"), 50, 0, 0)),
+            Err(TransactionError::SenderDoesNotExist {
+                sender: unknown.get_address()
+            })
+        );
+        assert_eq!(
+            blockchain.submit_transaction(Transaction::new(
+                alice.get_address(),
+                address("Bob"),
+                10,
+                0,
+                0
+            )),
+            Err(TransactionError::MissingPublicKey)
+        );
+        assert!(
+            blockchain.mempool.is_empty(),
+            "Rejected transactions should not be admitted"
+        );
+
+        // Pending transactions of the sender are spent first
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 60, 0, 0))
+            .unwrap();
+        assert_eq!(
+            blockchain.submit_transaction(signed_transaction(&alice, &address("Bob"), 50, 0, 1)),
+            Err(TransactionError::InsufficientBalance {
+                sender: alice.get_address(),
+                requested: 50,
+                available: 40,
+            })
+        );
+
+        // Transactions ahead of the sender's next nonce are held
+        let held = signed_transaction(&alice, &address("Bob"), 10, 0, 2);
+        assert_eq!(blockchain.submit_transaction(held.clone()), Ok(()));
+        assert!(blockchain.mempool.contains(&held.id()));
+    }
+    #[test]
+    fn full_mempool_keeps_the_highest_fees() {
+        // Arrange
+        let mut config = mock_config();
+        config.blockchain.max_mempool_size = 2;
+        let mut blockchain = Blockchain::new(config).unwrap();
+        let wallets = [Wallet::new(), Wallet::new(), Wallet::new()];
+        for wallet in &wallets {
+            blockchain.store.balances.insert(wallet.get_address(), 100);
+        }
+        let cheap = signed_transaction(&wallets[0], &address("Bob"), 10, 1, 0);
+        let expensive = signed_transaction(&wallets[1], &address("Bob"), 10, 5, 0);
+        let average = signed_transaction(&wallets[2], &address("Bob"), 10, 3, 0);
+
+        // Act
+        blockchain.submit_transaction(cheap.clone()).unwrap();
+        blockchain.submit_transaction(expensive.clone()).unwrap();
+        let result = blockchain.submit_transaction(average.clone());
+
+        // Assert
+        assert_eq!(result, Ok(()), "A higher fee should evict the lowest one");
+        assert_eq!(
+            blockchain.mempool.transactions(),
+            &[expensive.clone(), average],
+            "The cheapest transaction should be evicted"
+        );
+        assert_eq!(
+            blockchain.submit_transaction(cheap),
+            Err(TransactionError::MempoolFull { minimum_fee: 4 })
+        );
+    }
+    #[test]
+    fn leftover_transactions_stay_in_the_mempool_across_blocks() {
+        // Arrange: Alice's second transaction waits for her first one
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let held = signed_transaction(&alice, &address("Bob"), 10, 0, 1);
+        blockchain.submit_transaction(held.clone()).unwrap();
+
+        // Act
+        let result = blockchain.add_block(&miner());
+
+        // Assert
+        assert_eq!(result, Err(BlockchainError::NoValidTransactions));
+        assert_eq!(
+            blockchain.mempool.transactions(),
+            &[held],
+            "Held transactions should stay in the mempool"
+        );
+
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 0, 0))
+            .unwrap();
+        let block = blockchain.add_block(&miner()).unwrap();
+        assert_eq!(block.transactions.len(), 3, "Both transactions are mined");
+        assert!(blockchain.mempool.is_empty());
+    }
+    #[test]
+    fn block_holds_at_most_max_block_transactions() {
+        // Arrange: Room for the coinbase and two transactions
+        let mut config = mock_config();
+        config.blockchain.max_block_transactions = 3;
+        let mut blockchain = Blockchain::new(config).unwrap();
+        let wallets = [Wallet::new(), Wallet::new(), Wallet::new()];
+        for wallet in &wallets {
+            blockchain.store.balances.insert(wallet.get_address(), 100);
+        }
+        let cheap = signed_transaction(&wallets[0], &address("Bob"), 10, 1, 0);
+        let expensive = signed_transaction(&wallets[1], &address("Bob"), 10, 5, 0);
+        let average = signed_transaction(&wallets[2], &address("Bob"), 10, 3, 0);
+        for transaction in [&cheap, &expensive, &average] {
+            blockchain.submit_transaction(transaction.clone()).unwrap();
+        }
+
+        // Act
+        let block = blockchain.add_block(&miner()).unwrap();
+
+        // Assert
+        assert_eq!(
+            block.transactions[1..].to_vec(),
+            vec![expensive, average],
+            "The highest fees should fill the block"
+        );
+        assert_eq!(
+            blockchain.mempool.transactions(),
+            std::slice::from_ref(&cheap),
+            "Overflow transactions should stay queued"
+        );
+        let next_block = blockchain.add_block(&miner()).unwrap();
+        assert_eq!(next_block.transactions[1..].to_vec(), vec![cheap]);
+    }
+    #[test]
+    fn block_stays_within_max_block_size() {
+        // Arrange: Measure a block holding a single transfer
+        let (mut measured, alice) = blockchain_funding(&mock_config());
+        measured
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 0, 0))
+            .unwrap();
+        let single_transfer_size = measured.add_block(&miner()).unwrap().encode().len();
+
+        let mut config = mock_config();
+        config.blockchain.genesis_miner = alice.get_address();
+        config.blockchain.max_block_size = single_transfer_size + 16;
+        let mut blockchain = Blockchain::new(config).unwrap();
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 0, 0))
+            .unwrap();
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Carol"), 10, 0, 1))
+            .unwrap();
+
+        // Act
+        let block = blockchain.add_block(&miner()).unwrap();
+
+        // Assert
+        assert_eq!(block.transactions.len(), 2, "Only one transfer fits");
+        assert!(block.encode().len() <= single_transfer_size + 16);
+        assert_eq!(blockchain.mempool.len(), 1, "The other transfer is queued");
+        assert_eq!(
+            blockchain.add_block(&miner()).unwrap().transactions.len(),
+            2
+        );
+    }
+    #[test]
+    fn block_exceeding_the_limits_invalidates_chain() {
+        let config = mock_config();
+        let mut blockchain = blockchain_with_mined_block(config);
+        let block = blockchain.store.blocks[1].clone();
+
+        blockchain.max_block_transactions = 1;
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::TooManyTransactions {
+                index: 1,
+                max: 1,
+                found: 2,
+            })
+        );
+
+        blockchain.max_block_transactions = 2;
+        blockchain.max_block_size = block.encode().len() - 1;
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::BlockTooLarge {
+                index: 1,
+                max: block.encode().len() - 1,
+                found: block.encode().len(),
+            })
+        );
+    }
+    #[test]
+    fn address_history_has_directions_and_running_balances() {
+        // Arrange: Alice pays Bob twice, Bob pays Carol once
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let pre_mined = mock_config().blockchain.genesis_pre_mined;
+        let bob = Wallet::new();
+        let to_bob = signed_transaction(&alice, &bob.get_address(), 100, 2, 0);
+        blockchain.submit_transaction(to_bob.clone()).unwrap();
+        blockchain.add_block(&miner()).unwrap();
+        let again_to_bob = signed_transaction(&alice, &bob.get_address(), 50, 0, 1);
+        let to_carol = signed_transaction(&bob, &address("Carol"), 30, 1, 0);
+        blockchain.submit_transaction(again_to_bob.clone()).unwrap();
+        blockchain.submit_transaction(to_carol.clone()).unwrap();
+        blockchain.add_block(&miner()).unwrap();
+
+        // Act
+        let history = blockchain.get_address_history(&bob.get_address(), &HistoryQuery::default());
+
+        // Assert: Bob's transfer pays the higher fee, so it is executed first in block 2
+        let summary: Vec<(String, Direction, u64)> = history
+            .iter()
+            .map(|entry| (entry.id.clone(), entry.direction, entry.balance))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (again_to_bob.id(), Direction::Received, 119),
+                (to_carol.id(), Direction::Sent, 69),
+                (to_bob.id(), Direction::Received, 100),
+            ],
+            "History should be newest first with the balance after every transaction"
+        );
+        assert_eq!(
+            blockchain
+                .get_address_history(&alice.get_address(), &HistoryQuery::default())
+                .first()
+                .map(|entry| entry.balance),
+            Some(pre_mined - 152)
+        );
+        let page = blockchain.get_address_history(
+            &bob.get_address(),
+            &HistoryQuery {
+                to_height: 1,
+                ..HistoryQuery::default()
+            },
+        );
+        assert_eq!(page.len(), 1, "Only block 1 should be queried");
+        assert_eq!(page[0].id, to_bob.id());
+
+        // Disconnected blocks leave the history
+        blockchain.disconnect_tip().unwrap();
+        assert_eq!(
+            blockchain.get_transaction_history(&bob.get_address()),
+            vec![to_bob]
+        );
+    }
+    #[test]
+    fn beneficiary_history_includes_the_collected_fees() {
+        // Arrange
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 7, 0))
+            .unwrap();
+
+        // Act
+        let block = blockchain.add_block(&miner()).unwrap();
+
+        // Assert
+        let history = blockchain.get_address_history(&miner(), &HistoryQuery::default());
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, block.transactions[0].id());
+        assert_eq!(
+            Some(history[0].balance),
+            blockchain.store.balance(&miner()),
+            "The latest entry should hold the beneficiary's balance, fees included"
+        );
+        assert_eq!(history[0].balance, blockchain.block_subsidy(1) + 7);
+    }
+    #[test]
+    fn retrieve_transaction_history() {
+        // Initialize the blockchain using the mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        let charlie = Wallet::new();
+
+        // Add accounts and balances
+        blockchain.store.balances.insert(alice.get_address(), 200);
+        blockchain.store.balances.insert(bob.get_address(), 100);
+        blockchain.store.balances.insert(charlie.get_address(), 300);
+
+        // Add a few transactions
+        let alice_to_bob = signed_transaction(&alice, &bob.get_address(), 50, 0, 0);
+        blockchain.submit_transaction(alice_to_bob.clone()).unwrap();
+        blockchain.add_block(&miner()).unwrap(); // Block 1
+
+        let bob_to_charlie = signed_transaction(&bob, &charlie.get_address(), 30, 0, 0);
+        let alice_to_charlie = signed_transaction(&alice, &charlie.get_address(), 70, 0, 1);
+        blockchain
+            .submit_transaction(bob_to_charlie.clone())
+            .unwrap();
+        blockchain
+            .submit_transaction(alice_to_charlie.clone())
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap(); // Block 2
+
+        let charlie_to_alice = signed_transaction(&charlie, &alice.get_address(), 20, 0, 0);
+        blockchain
+            .submit_transaction(charlie_to_alice.clone())
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap(); // Block 3
+
+        // Get the transaction history for each address
+        let alice_history = blockchain.get_transaction_history(&alice.get_address());
+        let bob_history = blockchain.get_transaction_history(&bob.get_address());
+        let charlie_history = blockchain.get_transaction_history(&charlie.get_address());
+
+        // Verify Alice's transaction history
+        assert_eq!(
+            alice_history,
+            vec![
+                alice_to_bob.clone(),
+                alice_to_charlie.clone(),
+                charlie_to_alice.clone()
+            ],
+            "Alice's transaction history should include all transactions involving her as sender or receiver"
+        );
+
+        // Verify Bob's transaction history
+        assert_eq!(
+            bob_history,
+            vec![alice_to_bob, bob_to_charlie.clone()],
+            "Bob's transaction history should include all transactions involving him as sender or receiver"
+        );
+
+        // Verify Charlie's transaction history
+        assert_eq!(
+            charlie_history,
+            vec![bob_to_charlie, alice_to_charlie, charlie_to_alice],
+            "Charlie's transaction history should include all transactions involving him as sender or receiver"
+        );
+    }
+    #[test]
+    fn block_is_not_created_without_transactions() {
+        // Initialize the blockchain using the mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+
+        assert_eq!(
+            blockchain.store.blocks.len(),
+            1,
+            "Blockchain should start with the genesis block"
+        );
+
+        assert_eq!(
+            blockchain.add_block(&miner()),
+            Err(BlockchainError::NoValidTransactions)
+        );
+
+        assert_eq!(
+            blockchain.store.blocks.len(),
+            1,
+            "No new block should be created when there are no transactions"
+        );
+    }
+    #[test]
+    fn add_multiple_blocks_and_verify_chain_integrity_and_balances() {
+        // Initialize the blockchain using the mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        let charlie = Wallet::new();
+
+        // Set initial balances
+        blockchain.store.balances.insert(alice.get_address(), 300);
+        blockchain.store.balances.insert(bob.get_address(), 0);
+        blockchain.store.balances.insert(charlie.get_address(), 0);
+
+        // Add transactions to the mempool and add the first block
+        let alice_to_bob = signed_transaction(&alice, &bob.get_address(), 100, 0, 0);
+        blockchain.submit_transaction(alice_to_bob.clone()).unwrap();
+        blockchain.add_block(&miner()).unwrap();
+
+        // Add more transactions to the mempool and add the second block
+        let bob_to_charlie = signed_transaction(&bob, &charlie.get_address(), 50, 0, 0);
+        let alice_to_charlie = signed_transaction(&alice, &charlie.get_address(), 50, 0, 1);
+        blockchain
+            .submit_transaction(bob_to_charlie.clone())
+            .unwrap();
+        blockchain
+            .submit_transaction(alice_to_charlie.clone())
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+
+        // Check the chain length
+        assert_eq!(
+            blockchain.store.blocks.len(),
+            3,
+            "Blockchain should contain 3 blocks (genesis + 2 new blocks)"
+        );
+
+        // Validate the chain integrity
+        assert!(
+            blockchain.is_valid(),
+            "Blockchain should be valid after adding multiple blocks"
+        );
+
+        // Check account balances
+        assert_eq!(
+            blockchain.store.balances.get(&alice.get_address()).unwrap(),
+            &150,
+            "Alice's balance should be updated correctly"
+        );
+        assert_eq!(
+            blockchain.store.balances.get(&bob.get_address()).unwrap(),
+            &50,
+            "Bob's balance should be updated correctly"
+        );
+        assert_eq!(
+            blockchain
+                .store
+                .balances
+                .get(&charlie.get_address())
+                .unwrap(),
+            &100,
+            "Charlie's balance should be updated correctly"
+        );
+
+        // Verify transactions in blocks
+        let block_1 = &blockchain.store.blocks[1];
+        assert_eq!(
+            block_1.transactions.len(),
+            2,
+            "Block 1 should contain the coinbase and 1 transaction"
+        );
+        assert_eq!(
+            block_1.transactions[1], alice_to_bob,
+            "Block 1 transaction should match the expected transaction"
+        );
+
+        let block_2 = &blockchain.store.blocks[2];
+        assert_eq!(
+            block_2.transactions.len(),
+            3,
+            "Block 2 should contain the coinbase and 2 transactions"
+        );
+        assert_eq!(
+            block_2.transactions[1], bob_to_charlie,
+            "Block 2 first transaction should match the expected transaction"
+        );
+        assert_eq!(
+            block_2.transactions[2], alice_to_charlie,
+            "Block 2 second transaction should match the expected transaction"
+        );
+    }
+    #[test]
+    fn block_subsidy_halves_every_interval() {
+        let mut config = mock_config();
+        config.blockchain.initial_subsidy = 1_000;
+        config.blockchain.halving_interval = 10;
+        let blockchain = Blockchain::new(config.clone()).unwrap();
+
+        assert_eq!(blockchain.block_subsidy(1), 1_000, "First era pays in full");
+        assert_eq!(blockchain.block_subsidy(9), 1_000, "First era pays in full");
+        assert_eq!(blockchain.block_subsidy(10), 500, "Second era pays half");
+        assert_eq!(
+            blockchain.block_subsidy(25),
+            250,
+            "Third era pays a quarter"
+        );
+        assert_eq!(
+            blockchain.block_subsidy(10 * 64),
+            0,
+            "Subsidy should end after 64 halvings"
+        );
+
+        // Halving disabled
+        config.blockchain.halving_interval = 0;
+        let blockchain = Blockchain::new(config).unwrap();
+        assert_eq!(
+            blockchain.block_subsidy(u32::MAX),
+            1_000,
+            "Subsidy should never halve when the interval is 0"
+        );
+    }
+    #[test]
+    fn coinbase_is_limited_by_total_supply() {
+        let mut config = mock_config();
+        config.blockchain.initial_subsidy = 1_000;
+        config.token.total_supply = config.blockchain.genesis_pre_mined + 1_500;
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+
+        for nonce in 0..3 {
+            blockchain
+                .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 0, nonce))
+                .unwrap();
+            blockchain.add_block(&miner()).unwrap();
+        }
+
+        let minted: Vec<u64> = blockchain.store.blocks[1..]
+            .iter()
+            .map(|block| block.transactions[0].amount)
+            .collect();
+        assert_eq!(
+            minted,
+            vec![1_000, 500, 0],
+            "Coinbase should only mint the remaining supply"
+        );
+        assert_eq!(
+            blockchain.circulating_supply, config.token.total_supply,
+            "Circulating supply should reach the total supply"
+        );
+        assert_eq!(
+            blockchain.store.balances[&miner()],
+            1_500,
+            "The beneficiary should receive the minted coins"
+        );
+        assert!(blockchain.is_valid(), "Blockchain should be valid");
+    }
+    #[test]
+    fn coinbase_exceeding_subsidy_invalidates_chain() {
+        let config = mock_config();
+        let mut blockchain = blockchain_with_mined_block(config.clone());
+
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.transactions[0].amount = config.blockchain.initial_subsidy + 1;
+        rehash(block);
+
+        assert!(
+            !blockchain.is_valid(),
+            "The blockchain should be invalid when a coinbase exceeds the subsidy"
+        );
+    }
+    #[test]
+    fn block_with_expired_transaction_is_rejected() {
+        // Arrange: A transaction valid until the genesis block only
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let mut expired =
+            Transaction::new(alice.get_address(), address("Bob"), 10, 0, 0).with_valid_until(0);
+        expired.sign(
+            alice.sign_transaction(&expired.signing_payload()),
+            alice.public_key,
+        );
+        let genesis = blockchain.store.blocks[0].clone();
+        let block = mine_on(&blockchain, &genesis, vec![expired.clone()]);
+
+        // Act & Assert
+        assert_eq!(
+            blockchain.receive_block(block),
+            Err(BlockchainError::BlockValidation(
+                BlockValidationError::InvalidTransaction {
+                    index: 1,
+                    error: TransactionError::Expired {
+                        valid_until: 0,
+                        height: 1,
+                    },
+                }
+            ))
+        );
+        assert_eq!(
+            blockchain.submit_transaction(expired),
+            Err(TransactionError::Expired {
+                valid_until: 0,
+                height: 1,
+            }),
+            "Expired transactions should not be admitted"
+        );
+    }
+    #[test]
+    fn expiring_transactions_are_dropped_from_the_mempool() {
+        // Arrange: Alice's held transaction may only be mined in block 1
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let bob = Wallet::new();
+        blockchain.store.balances.insert(bob.get_address(), 100);
+        let mut held =
+            Transaction::new(alice.get_address(), address("Carol"), 10, 0, 1).with_valid_until(1);
+        held.sign(
+            alice.sign_transaction(&held.signing_payload()),
+            alice.public_key,
+        );
+        blockchain.submit_transaction(held.clone()).unwrap();
+        blockchain
+            .submit_transaction(signed_transaction(&bob, &address("Carol"), 10, 0, 0))
+            .unwrap();
+
+        // Act
+        let block = blockchain.add_block(&miner()).unwrap();
+        blockchain.prune_mempool();
+
+        // Assert
+        assert!(!block.transactions.contains(&held));
+        assert!(
+            blockchain.mempool.is_empty(),
+            "Transactions expiring before the next block should be dropped"
+        );
+    }
+    #[test]
+    fn coinbase_exceeding_total_supply_invalidates_chain() {
+        let mut config = mock_config();
+        config.token.total_supply = config.blockchain.genesis_pre_mined + 100;
+        let mut blockchain = blockchain_with_mined_block(config.clone());
+        assert_eq!(
+            blockchain.store.blocks[1].transactions[0].amount, 100,
+            "Coinbase should be limited to the remaining supply"
+        );
+
+        // Still within the subsidy, but above the total supply
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.transactions[0].amount = 200;
+        rehash(block);
+
+        assert!(
+            !blockchain.is_valid(),
+            "The blockchain should be invalid when a coinbase exceeds the total supply"
+        );
+    }
+    #[test]
+    fn block_without_single_coinbase_invalidates_chain() {
+        let config = mock_config();
+
+        // Coinbase removed
+        let mut blockchain = blockchain_with_mined_block(config.clone());
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.transactions.remove(0);
+        rehash(block);
+        assert!(
+            !blockchain.is_valid(),
+            "The blockchain should be invalid when a block has no coinbase"
+        );
+
+        // Second coinbase added
+        let mut blockchain = blockchain_with_mined_block(config.clone());
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block
+            .transactions
+            .push(Transaction::coinbase(miner(), 1, 1));
+        rehash(block);
+        assert!(
+            !blockchain.is_valid(),
+            "The blockchain should be invalid when a block has two coinbases"
+        );
+
+        // Coinbase paid to someone else than the beneficiary
+        let mut blockchain = blockchain_with_mined_block(config);
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.transactions[0].receiver = "Thief".to_string();
+        rehash(block);
+        assert!(
+            !blockchain.is_valid(),
+            "The blockchain should be invalid when the coinbase is not paid to the beneficiary"
+        );
+    }
+    #[test]
+    fn malformed_coinbase_invalidates_chain() {
+        let config = mock_config();
+
+        // Coinbase nonce is not the block height
+        let mut blockchain = blockchain_with_mined_block(config.clone());
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.transactions[0].nonce = 2;
+        rehash(block);
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::CoinbaseNonceNotHeight { index: 1, found: 2 }),
+            "The blockchain should be invalid when a coinbase nonce is not the block height"
+        );
+
+        // Coinbase pays a fee
+        let mut blockchain = blockchain_with_mined_block(config.clone());
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.transactions[0].fee = 1;
+        rehash(block);
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::MalformedCoinbase { index: 1 }),
+            "The blockchain should be invalid when a coinbase pays a fee"
+        );
+
+        // Coinbase is signed
+        let mut blockchain = blockchain_with_mined_block(config);
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        Wallet::new().sign(&mut block.transactions[0]);
+        rehash(block);
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::MalformedCoinbase { index: 1 }),
+            "The blockchain should be invalid when a coinbase carries a signature"
+        );
+    }
+    #[test]
+    fn block_with_invalid_beneficiary_is_rejected() {
+        let testnet = Wallet::new().with_network(Network::Testnet).get_address();
+
+        for beneficiary in ["peer".to_string(), testnet] {
+            // Arrange: A block paying its coinbase to its malformed beneficiary
+            let mut blockchain = Blockchain::new(mock_config()).unwrap();
+            let genesis = blockchain.store.blocks[0].clone();
+            let mut block = mine_on(&blockchain, &genesis, vec![]);
+            block.beneficiary = beneficiary.clone();
+            block.transactions[0].receiver = beneficiary.clone();
+            rehash(&mut block);
+
+            // Act & Assert
+            assert!(
+                matches!(
+                    blockchain.receive_block(block),
+                    Err(BlockchainError::BlockValidation(
+                        BlockValidationError::InvalidBeneficiary { index: 1, ref address, .. }
+                    )) if *address == beneficiary
+                ),
+                "Block paying {} should be rejected",
+                beneficiary
+            );
+        }
+    }
+    #[test]
+    fn transaction_that_causes_balance_overflow_is_rejected() {
+        // Initialize the blockchain using the mock configuration
+        let config = mock_config();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+
+        // Set up an account with a balance near u64::MAX
+        let near_max_balance = u64::MAX - 10;
+        blockchain
+            .store
+            .balances
+            .insert(alice.get_address(), near_max_balance);
+        blockchain.store.balances.insert(bob.get_address(), 11);
+
+        // Submit a transaction that would cause Alice's balance to overflow
+        assert_eq!(
+            blockchain.submit_transaction(signed_transaction(&bob, &alice.get_address(), 11, 0, 0)),
+            Err(TransactionError::BalanceOverflow),
+            "The overflowing transaction should not be admitted"
+        );
+
+        // Attempt to add a block
+        assert_eq!(
+            blockchain.add_block(&miner()),
+            Err(BlockchainError::NoValidTransactions)
+        );
+
+        // Ensure the blockchain length remains 1 (only the genesis block)
+        assert_eq!(
+            blockchain.store.blocks.len(),
+            1,
+            "Blockchain should not add a block with an overflowing transaction"
+        );
+
+        // Ensure balances remain unchanged
+        assert_eq!(
+            blockchain.store.balances.get(&alice.get_address()).unwrap(),
             &near_max_balance,
             "Alice's balance should remain unchanged"
         );
         assert_eq!(
-            blockchain.accounts.get("Bob").unwrap(),
+            blockchain.store.balances.get(&bob.get_address()).unwrap(),
             &11,
             "Bob's balance should remain unchanged"
         );
@@ -960,9 +3116,727 @@ mod tests {
         );
 
         // Ensure the blockchain is still valid
+        assert!(blockchain.is_valid(), "Blockchain should still be valid");
+    }
+    #[test]
+    fn open_restores_stored_blocks_and_state() {
+        // Arrange: Fund Alice through the genesis pre-mine, so the state can be replayed
+        let data_dir = tempdir().unwrap();
+        let alice = Wallet::new();
+        let mut config = mock_config();
+        config.blockchain.genesis_miner = alice.get_address();
+
+        let mut blockchain = Blockchain::open(config.clone(), data_dir.path()).unwrap();
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 100, 5, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+        assert_eq!(blockchain.store.len(), 2, "Block should be mined");
+        let chain: Vec<Block> = blockchain.store.blocks().cloned().collect();
+        let circulating_supply = blockchain.circulating_supply;
+        drop(blockchain);
+
+        // Act
+        let mut reopened = Blockchain::open(config.clone(), data_dir.path()).unwrap();
+
+        // Assert
+        assert_eq!(
+            reopened.store.blocks().cloned().collect::<Vec<Block>>(),
+            chain,
+            "Stored blocks should be restored"
+        );
+        assert_eq!(
+            reopened.store.balance(&alice.get_address()),
+            Some(config.blockchain.genesis_pre_mined - 105),
+            "Sender balance should be replayed"
+        );
+        assert_eq!(reopened.store.balance(&address("Bob")), Some(100));
+        assert_eq!(
+            reopened.store.balance(&miner()),
+            Some(reopened.block_subsidy(1) + 5),
+            "Coinbase and fees should be replayed"
+        );
+        assert_eq!(
+            reopened.store.nonce(&alice.get_address()),
+            1,
+            "Nonces should be replayed"
+        );
+        assert_eq!(
+            reopened.circulating_supply, circulating_supply,
+            "Circulating supply should be replayed"
+        );
+
+        // Blocks mined after reopening are stored as well
+        reopened
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 50, 0, 1))
+            .unwrap();
+        reopened.add_block(&miner()).unwrap();
+        drop(reopened);
+        let reopened = Blockchain::open(config, data_dir.path()).unwrap();
+        assert_eq!(reopened.store.len(), 3, "Third block should be restored");
+        assert_eq!(reopened.store.balance(&address("Bob")), Some(150));
+    }
+    #[test]
+    fn open_rejects_invalid_stored_chain() {
+        // Arrange: Store a chain whose coinbase exceeds the block subsidy
+        let config = mock_config();
+        let mut chain = blockchain_with_mined_block(config.clone()).store.blocks;
+        chain[1].transactions[0].amount += 1;
+        rehash(&mut chain[1]);
+
+        let data_dir = tempdir().unwrap();
+        let mut store = FileStore::open(data_dir.path()).unwrap();
+        for block in chain {
+            store.append_block(block).unwrap();
+        }
+        drop(store);
+
+        // Act & Assert
+        assert_eq!(
+            Blockchain::open(config, data_dir.path()).err(),
+            Some(BlockchainError::BlockValidation(
+                BlockValidationError::CoinbaseExceedsSubsidy {
+                    index: 1,
+                    subsidy: 5_000_000_000,
+                    found: 5_000_000_001,
+                }
+            )),
+            "Invalid stored chain should be rejected"
+        );
+    }
+    #[test]
+    fn with_store_replays_blocks_of_any_backend() {
+        // Arrange: Move the blocks of a funded chain into a fresh store
+        let alice = Wallet::new();
+        let mut config = mock_config();
+        config.blockchain.genesis_miner = alice.get_address();
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 0, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+
+        let store = MemoryStore {
+            blocks: blockchain.store.blocks.clone(),
+            ..MemoryStore::default()
+        };
+
+        // Act
+        let restored = Blockchain::with_store(config, store).unwrap();
+
+        // Assert
+        assert_eq!(
+            restored.store.balances, blockchain.store.balances,
+            "Balances should be replayed"
+        );
+        assert_eq!(restored.store.nonces, blockchain.store.nonces);
+        assert_eq!(restored.circulating_supply, blockchain.circulating_supply);
+        assert_eq!(
+            restored.store.undos, blockchain.store.undos,
+            "Missing undo records should be stored while replaying"
+        );
+    }
+    #[test]
+    fn restore_rejects_stored_transactions_without_funds() {
+        // Arrange: Alice is funded outside of the chain, so replaying her transfer fails
+        let config = mock_config();
+        let store = MemoryStore {
+            blocks: blockchain_with_mined_block(config.clone()).store.blocks,
+            ..MemoryStore::default()
+        };
+
+        // Act
+        let result = Blockchain::with_store(config, store);
+
+        // Assert
         assert!(
-            blockchain.is_valid(),
-            "Blockchain should still be valid"
+            matches!(
+                result,
+                Err(BlockchainError::BlockValidation(
+                    BlockValidationError::InvalidTransaction { index: 1, .. }
+                ))
+            ),
+            "Replaying an unfunded transfer should fail"
+        );
+    }
+    #[test]
+    fn open_rejects_genesis_of_another_configuration() {
+        let data_dir = tempdir().unwrap();
+        Blockchain::open(mock_config(), data_dir.path()).unwrap();
+
+        let mut config = mock_config();
+        config.blockchain.genesis_hash = "other_genesis".to_string();
+
+        assert_eq!(
+            Blockchain::open(config, data_dir.path()).err(),
+            Some(BlockValidationError::GenesisMismatch.into()),
+            "Genesis block of another chain should be rejected"
+        );
+    }
+    #[test]
+    fn target_is_kept_without_retargeting() {
+        let mut config = mock_config();
+        config.blockchain.retarget_interval = 0;
+        let mut blockchain = Blockchain::new(config.clone()).unwrap();
+
+        let bits = mine_blocks(&mut blockchain, 3);
+
+        assert_eq!(
+            bits,
+            vec![config.blockchain.genesis_bits; 4],
+            "Every block should keep the genesis target"
+        );
+        assert!(blockchain.is_valid(), "Blockchain should be valid");
+    }
+    #[test]
+    fn target_drops_when_blocks_come_too_fast() {
+        // Arrange: Blocks are mined within seconds, far below the 600 seconds target
+        let mut config = mock_config();
+        config.blockchain.genesis_bits = EASY_BITS;
+        config.blockchain.retarget_interval = 2;
+        let mut blockchain = Blockchain::new(config).unwrap();
+
+        // Act
+        let bits = mine_blocks(&mut blockchain, 4);
+
+        // Assert: Retargets happen at heights 2 and 4, dividing the target by four each
+        let genesis_target = compact_to_target(EASY_BITS).unwrap();
+        let first_retarget = target_to_compact(genesis_target >> 2);
+        let second_retarget = target_to_compact(compact_to_target(first_retarget).unwrap() >> 2);
+        assert_eq!(
+            bits,
+            vec![
+                EASY_BITS,
+                EASY_BITS,
+                first_retarget,
+                first_retarget,
+                second_retarget
+            ],
+            "Target should be divided by four at every retarget"
+        );
+        assert_eq!(blockchain.next_bits(), second_retarget);
+        assert!(blockchain.is_valid(), "Retargeted blocks should be valid");
+    }
+    #[test]
+    fn retarget_compares_block_times_with_target_interval() {
+        let mut config = mock_config();
+        config.blockchain.retarget_interval = 2;
+        config.blockchain.target_block_interval = 600;
+        let blockchain = Blockchain::new(config.clone()).unwrap();
+        let bits = 0x1f00_ffff;
+        let target = compact_to_target(bits).unwrap();
+
+        // One interval of 600 seconds is on target
+        let on_target = [
+            block_at(0, bits),
+            block_at(600, bits),
+            block_at(3_000, bits),
+        ];
+        let on_target: Vec<&Block> = on_target.iter().collect();
+        assert_eq!(blockchain.expected_bits(&on_target[..2]), bits);
+        assert_eq!(
+            blockchain.expected_bits(&on_target),
+            bits,
+            "Retarget happens only every 2 blocks"
+        );
+
+        // Blocks that came 50% too slow raise the target by 50%
+        let slow = [block_at(0, bits), block_at(900, bits)];
+        let slow: Vec<&Block> = slow.iter().collect();
+        assert_eq!(
+            blockchain.expected_bits(&slow),
+            target_to_compact(target.mul_div(3, 2).unwrap())
+        );
+
+        // Later retargets measure the last `retarget_interval` intervals only
+        let fast = [
+            block_at(0, bits),
+            block_at(10_000, bits),
+            block_at(10_300, bits),
+            block_at(10_600, bits),
+        ];
+        let fast: Vec<&Block> = fast.iter().collect();
+        assert_eq!(
+            compact_to_target(blockchain.expected_bits(&fast)),
+            Some(target >> 1),
+            "Two intervals of 300 seconds are twice as fast as the target"
+        );
+
+        // The genesis target is the easiest target allowed
+        let genesis_bits = config.blockchain.genesis_bits;
+        let slowest = [block_at(0, genesis_bits), block_at(100_000, genesis_bits)];
+        let slowest: Vec<&Block> = slowest.iter().collect();
+        assert_eq!(blockchain.expected_bits(&slowest), genesis_bits);
+    }
+    #[test]
+    fn chain_work_sums_block_work() {
+        let blockchain = blockchain_with_mined_block(mock_config());
+
+        assert_eq!(
+            blockchain.chain_work(),
+            U256::from_u64(512),
+            "Two blocks with work 256 each"
+        );
+    }
+    #[test]
+    fn block_with_unexpected_target_invalidates_chain() {
+        let mut blockchain = blockchain_with_mined_block(mock_config());
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.bits = EASY_BITS;
+        rehash(block);
+
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::UnexpectedTarget {
+                index: 1,
+                expected: mock_config().blockchain.genesis_bits,
+                found: EASY_BITS
+            }),
+            "Blockchain should be invalid when a block is mined with an easier target"
+        );
+    }
+    #[test]
+    fn block_not_meeting_its_target_invalidates_chain() {
+        let mut blockchain = blockchain_with_mined_block(mock_config());
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        while block.meets_target() {
+            block.nonce += 1;
+            block.hash = block.calculate_hash();
+        }
+
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::TargetNotMet { index: 1 }),
+            "Blockchain should be invalid when a block hash exceeds its target"
+        );
+    }
+    #[test]
+    fn invalid_genesis_bits_are_rejected() {
+        let mut config = mock_config();
+        config.blockchain.genesis_bits = 0;
+
+        assert_eq!(
+            Blockchain::new(config).err(),
+            Some(ConfigError::InvalidGenesisBits { bits: 0 }.into())
+        );
+    }
+    #[test]
+    fn invalid_genesis_miner_is_rejected() {
+        let mut config = mock_config();
+        config.blockchain.genesis_miner = "MINER".to_string();
+
+        assert!(
+            matches!(
+                Blockchain::new(config).err(),
+                Some(BlockchainError::Config(ConfigError::InvalidGenesisMiner { address, .. }))
+                    if address == "MINER"
+            ),
+            "A genesis miner that is not an address should be rejected"
+        );
+    }
+    #[test]
+    fn block_with_unexpected_index_invalidates_chain() {
+        let mut blockchain = blockchain_with_mined_block(mock_config());
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.index = 5;
+        rehash(block);
+
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::UnexpectedIndex {
+                expected: 1,
+                found: 5
+            }),
+            "Block index should follow its previous block"
+        );
+    }
+    #[test]
+    fn block_not_after_median_time_invalidates_chain() {
+        // Arrange: Three blocks, so the median time is the time of the first block
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        mine_blocks(&mut blockchain, 2);
+        let median_time = blockchain.store.blocks[1].timestamp.clone();
+
+        // Act: Backdate the last block to the median time
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.timestamp = median_time;
+        rehash(block);
+
+        // Assert
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::TimestampNotAfterMedian { index: 2 }),
+            "Block should be timestamped after the median time"
+        );
+    }
+    #[test]
+    fn block_too_far_in_future_invalidates_chain() {
+        let mut blockchain = blockchain_with_mined_block(mock_config());
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.timestamp =
+            (Utc::now() + TimeDelta::seconds(MAX_FUTURE_BLOCK_TIME + 60)).to_rfc3339();
+        rehash(block);
+
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::TimestampTooFarInFuture { index: 1 }),
+            "Block should not be timestamped too far ahead of the local clock"
+        );
+
+        // Within the allowed drift
+        let block = blockchain.store.blocks.last_mut().unwrap();
+        block.timestamp = (Utc::now() + TimeDelta::seconds(60)).to_rfc3339();
+        rehash(block);
+        assert_eq!(blockchain.validate(), Ok(()));
+    }
+    #[test]
+    fn genesis_not_matching_configuration_invalidates_chain() {
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        let genesis = blockchain.store.blocks.first_mut().unwrap();
+        genesis.beneficiary = "Mallory".to_string();
+        rehash(genesis);
+
+        assert_eq!(
+            blockchain.validate(),
+            Err(BlockValidationError::GenesisMismatch),
+            "Genesis block should be mined by the configured genesis miner"
+        );
+    }
+    #[test]
+    fn cancelled_mining_returns_transactions_to_mempool() {
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let transaction = signed_transaction(&alice, &address("Bob"), 10, 0, 0);
+        blockchain.submit_transaction(transaction.clone()).unwrap();
+
+        // The next mining job is cancelled before it starts
+        let handle = blockchain.miner.cancel_handle();
+        handle.cancel();
+        assert_eq!(
+            blockchain.add_block(&miner()),
+            Err(BlockchainError::MiningCancelled { index: 1 })
+        );
+
+        assert_eq!(blockchain.store.len(), 1, "No block should be added");
+        assert_eq!(
+            blockchain.mempool.transactions(),
+            vec![transaction],
+            "Transactions should return to the mempool"
+        );
+        assert_eq!(blockchain.store.balance(&address("Bob")), None);
+
+        // The next block is mined again, even after cancelling through the stale handle
+        handle.cancel();
+        blockchain.add_block(&miner()).unwrap();
+        assert_eq!(blockchain.store.len(), 2, "Block should be mined");
+        assert_eq!(blockchain.store.balance(&address("Bob")), Some(10));
+    }
+    #[test]
+    fn received_block_extending_the_tip_is_connected() {
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let transaction = signed_transaction(&alice, &address("Bob"), 10, 0, 0);
+        blockchain.submit_transaction(transaction.clone()).unwrap();
+        let genesis = blockchain.store.blocks[0].clone();
+
+        let block = mine_on(&blockchain, &genesis, vec![transaction]);
+        let status = blockchain.receive_block(block.clone());
+
+        assert_eq!(status, Ok(BlockStatus::Connected));
+        assert_eq!(blockchain.store.last_block(), Some(&block));
+        assert_eq!(blockchain.store.balance(&address("Bob")), Some(10));
+        assert_eq!(
+            blockchain.mempool.transactions(),
+            vec![],
+            "Mined transactions leave the mempool"
+        );
+        assert_eq!(
+            blockchain.receive_block(block),
+            Err(BlockValidationError::DuplicateBlock { index: 1 }.into()),
+            "Known blocks should be rejected"
+        );
+    }
+    #[test]
+    fn received_block_without_known_parent_is_rejected() {
+        let (mut blockchain, _) = blockchain_funding(&mock_config());
+        let stranger = Block::new(0, vec![], "another chain".to_string(), miner(), EASY_BITS);
+
+        let block = mine_on(&blockchain, &stranger, vec![]);
+
+        assert_eq!(
+            blockchain.receive_block(block),
+            Err(BlockValidationError::UnknownParent { index: 1 }.into())
+        );
+        assert!(blockchain.block_tree.is_empty());
+    }
+    #[test]
+    fn get_transaction_reports_location_and_confirmations() {
+        // Arrange
+        let config = mock_config();
+        let (mut blockchain, alice) = blockchain_funding(&config);
+        let transfer = signed_transaction(&alice, &address("Bob"), 10, 0, 0);
+        blockchain.submit_transaction(transfer.clone()).unwrap();
+
+        // Act & Assert: Pending transactions have no confirmations
+        assert_eq!(
+            blockchain.get_transaction(&transfer.id()),
+            Some(IndexedTransaction {
+                transaction: transfer.clone(),
+                location: None,
+                confirmations: 0,
+            })
+        );
+
+        blockchain.add_block(&miner()).unwrap();
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 0, 1))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+        let expected = IndexedTransaction {
+            transaction: transfer.clone(),
+            location: Some(TransactionLocation {
+                height: 1,
+                position: 1,
+            }),
+            confirmations: 2,
+        };
+        assert_eq!(
+            blockchain.get_transaction(&transfer.id()),
+            Some(expected.clone()),
+            "Every block on top adds a confirmation"
+        );
+        assert_eq!(blockchain.get_transaction("unknown"), None);
+
+        // The index is rebuilt when the chain is restored
+        let store = MemoryStore {
+            blocks: blockchain.store.blocks.clone(),
+            ..MemoryStore::default()
+        };
+        let mut config = config;
+        config.blockchain.genesis_miner = alice.get_address();
+        let restored = Blockchain::with_store(config, store).unwrap();
+        assert_eq!(restored.get_transaction(&transfer.id()), Some(expected));
+        assert_eq!(restored.transaction_index.len(), 4);
+    }
+    #[test]
+    fn block_repeating_transactions_does_not_shadow_the_genuine_block() {
+        // Arrange: Repeating the last of three transactions keeps the Merkle root and hash
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let genesis = blockchain.store.blocks[0].clone();
+        let to_bob = signed_transaction(&alice, &address("Bob"), 10, 0, 0);
+        let to_carol = signed_transaction(&alice, &address("Carol"), 10, 0, 1);
+        let genuine = mine_on(&blockchain, &genesis, vec![to_bob, to_carol.clone()]);
+        let mut mutated = genuine.clone();
+        mutated.transactions.push(to_carol.clone());
+        assert_eq!(mutated.calculate_hash(), genuine.hash);
+
+        // Act & Assert
+        assert_eq!(
+            blockchain.receive_block(mutated),
+            Err(BlockValidationError::DuplicateTransaction {
+                index: 1,
+                id: to_carol.id(),
+            }
+            .into())
+        );
+        assert!(blockchain.block_tree.is_empty());
+        assert_eq!(
+            blockchain.receive_block(genuine.clone()),
+            Ok(BlockStatus::Connected),
+            "The genuine block should still be accepted"
+        );
+        assert_eq!(blockchain.store.last_block(), Some(&genuine));
+    }
+    #[test]
+    fn heavier_branch_reorganizes_the_chain() {
+        // Arrange: The main chain pays Bob, a competing branch pays Carol instead
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let erin = Wallet::new();
+        blockchain.store.balances.insert(erin.get_address(), 100);
+        let genesis = blockchain.store.blocks[0].clone();
+        let to_bob = signed_transaction(&alice, &address("Bob"), 10, 0, 0);
+        let to_dave = signed_transaction(&alice, &address("Dave"), 5, 0, 1);
+        let from_erin = signed_transaction(&erin, &address("Frank"), 30, 0, 0);
+        blockchain.submit_transaction(to_bob.clone()).unwrap();
+        blockchain.submit_transaction(to_dave.clone()).unwrap();
+        blockchain.submit_transaction(from_erin.clone()).unwrap();
+        blockchain.add_block(&miner()).unwrap();
+        let main_block = blockchain.store.blocks[1].clone();
+
+        let to_carol = signed_transaction(&alice, &address("Carol"), 20, 0, 0);
+        let first = mine_on(&blockchain, &genesis, vec![to_carol, to_dave.clone()]);
+        let second = mine_on(&blockchain, &first, vec![]);
+
+        // Act & Assert: A branch with equal work stays on the side
+        assert_eq!(
+            blockchain.receive_block(first.clone()),
+            Ok(BlockStatus::SideBranch)
+        );
+        assert_eq!(blockchain.store.last_block(), Some(&main_block));
+        assert_eq!(blockchain.store.balance(&address("Carol")), None);
+
+        // A branch with more work becomes the main chain
+        assert_eq!(
+            blockchain.receive_block(second.clone()),
+            Ok(BlockStatus::Reorganized {
+                disconnected: 1,
+                connected: 2
+            })
+        );
+        assert_eq!(
+            blockchain.store.blocks,
+            vec![genesis, first, second],
+            "Branch should replace the main chain"
+        );
+        assert_eq!(
+            blockchain.store.balance(&address("Bob")),
+            None,
+            "Bob is paid no more"
+        );
+        assert_eq!(blockchain.store.balance(&address("Carol")), Some(20));
+        assert_eq!(blockchain.store.balance(&address("Dave")), Some(5));
+        assert_eq!(
+            blockchain.store.balance(&miner()),
+            None,
+            "Main chain reward is gone"
+        );
+        assert_eq!(
+            blockchain.circulating_supply,
+            mock_config().blockchain.genesis_pre_mined + 2 * blockchain.block_subsidy(1)
+        );
+        assert_eq!(
+            blockchain.mempool.transactions(),
+            vec![from_erin],
+            "Orphaned transactions missing from the branch should return to the mempool, \
+             unless the branch spent their nonce"
+        );
+        assert_eq!(
+            blockchain.get_transaction(&to_dave.id()).unwrap().location,
+            Some(TransactionLocation {
+                height: 1,
+                position: 2
+            }),
+            "Index should follow the reorganized chain"
+        );
+        assert_eq!(
+            blockchain.get_transaction(&to_bob.id()),
+            None,
+            "Double spent transactions should be dropped"
+        );
+        assert!(
+            blockchain.block_tree.contains(&main_block.hash),
+            "Disconnected block should stay on a side branch"
+        );
+        assert_eq!(blockchain.block_tree.len(), 1);
+        assert!(blockchain.is_valid(), "Reorganized chain should be valid");
+    }
+    #[test]
+    fn branch_with_invalid_transaction_keeps_the_main_chain() {
+        // Arrange: The second branch block spends from an unfunded wallet
+        let (mut blockchain, alice) = blockchain_funding(&mock_config());
+        let genesis = blockchain.store.blocks[0].clone();
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 0, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+        let main_chain = blockchain.store.blocks.clone();
+        let balances = blockchain.store.balances.clone();
+        let pending = signed_transaction(&alice, &address("Dave"), 5, 0, 1);
+        blockchain.submit_transaction(pending.clone()).unwrap();
+
+        let first = mine_on(
+            &blockchain,
+            &genesis,
+            vec![
+                signed_transaction(&alice, &address("Carol"), 20, 0, 0),
+                pending.clone(),
+            ],
+        );
+        let unfunded = signed_transaction(&Wallet::new(), &address("Carol"), 1, 0, 0);
+        let second = mine_on(&blockchain, &first, vec![unfunded]);
+        blockchain.receive_block(first.clone()).unwrap();
+
+        // Act
+        let result = blockchain.receive_block(second.clone());
+
+        // Assert
+        assert!(
+            matches!(
+                result,
+                Err(BlockchainError::BlockValidation(
+                    BlockValidationError::InvalidTransaction { index: 2, .. }
+                ))
+            ),
+            "Invalid branch block should be rejected"
+        );
+        assert_eq!(
+            blockchain.store.blocks, main_chain,
+            "Main chain should be restored"
+        );
+        assert_eq!(
+            blockchain.store.balances, balances,
+            "Account state should be restored"
+        );
+        assert_eq!(
+            blockchain.mempool.transactions(),
+            vec![pending],
+            "Pending transactions of the connected branch blocks should return to the mempool"
+        );
+        assert!(blockchain.block_tree.contains(&first.hash));
+        assert!(
+            !blockchain.block_tree.contains(&second.hash),
+            "Invalid block should be dropped"
+        );
+        assert!(blockchain.is_valid());
+    }
+    #[test]
+    fn disconnecting_blocks_restores_the_account_state() {
+        // Arrange
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+        let balances = blockchain.store.balances.clone();
+        let circulating_supply = blockchain.circulating_supply;
+
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 10, 2, 0))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+        let balances_after_first = blockchain.store.balances.clone();
+        blockchain
+            .submit_transaction(signed_transaction(&alice, &address("Bob"), 20, 0, 1))
+            .unwrap();
+        blockchain.add_block(&miner()).unwrap();
+        assert_eq!(
+            blockchain.store.undos.len(),
+            2,
+            "Every block has an undo record"
+        );
+        assert_eq!(
+            blockchain.store.undos[0].accounts[0],
+            AccountUndo {
+                address: miner(),
+                balance: None,
+                nonce: 0
+            },
+            "Undo record should note accounts created by the block"
+        );
+
+        // Act & Assert
+        let second = blockchain.disconnect_tip().unwrap();
+        assert_eq!(second.index, 2);
+        assert_eq!(blockchain.store.balances, balances_after_first);
+        assert_eq!(blockchain.store.nonce(&alice.get_address()), 1);
+
+        blockchain.disconnect_tip().unwrap();
+        assert_eq!(
+            blockchain.store.balances, balances,
+            "Balances should be restored and created accounts removed"
+        );
+        assert_eq!(blockchain.store.nonce(&alice.get_address()), 0);
+        assert_eq!(blockchain.circulating_supply, circulating_supply);
+        assert_eq!(blockchain.store.undos, vec![]);
+        assert_eq!(
+            blockchain.disconnect_tip(),
+            Err(BlockchainError::GenesisCannotBeDisconnected)
         );
     }
 }
@@ -0,0 +1,296 @@
+use crate::core::transaction::Transaction;
+use crate::errors::transaction_errors::TransactionError;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::HashMap;
+
+/// Pending transactions waiting to be included in a block.
+/// - Holds every transaction once, identified by its id.
+/// - Holds at most `max_size` transactions. When full, a new transaction evicts the one
+///   paying the lowest fee if it pays more, and is rejected otherwise. Transactions that a
+///   later nonce of their sender depends on are not evicted.
+/// - Drops transactions waiting longer than `ttl` seconds when `remove_stale` is called.
+/// - Does not validate transactions against the chain; `Blockchain::submit_transaction`
+///   does before admitting them.
+#[derive(Debug, Clone)]
+pub struct Mempool {
+    transactions: Vec<Transaction>,           // In order of arrival
+    admitted: HashMap<String, DateTime<Utc>>, // Admission time by transaction id
+    max_size: usize,
+    ttl: u64, // Seconds a transaction may wait (0 disables the TTL)
+}
+
+impl Mempool {
+    /// Creates an empty mempool holding at most `max_size` transactions for at most `ttl`
+    /// seconds each (0 disables the TTL).
+    pub fn new(max_size: usize, ttl: u64) -> Self {
+        Mempool {
+            transactions: Vec::new(),
+            admitted: HashMap::new(),
+            max_size,
+            ttl,
+        }
+    }
+
+    /// Returns the number of pending transactions.
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Returns `true` if no transaction is pending.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Returns the maximum number of pending transactions.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Returns `true` if the transaction with the given id is pending.
+    pub fn contains(&self, id: &str) -> bool {
+        self.admitted.contains_key(id)
+    }
+
+    /// Returns the pending transaction with the given id.
+    pub fn get(&self, id: &str) -> Option<&Transaction> {
+        if !self.contains(id) {
+            return None;
+        }
+        self.transactions
+            .iter()
+            .find(|transaction| transaction.id() == id)
+    }
+
+    /// Returns the pending transactions in order of arrival.
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// Returns the lowest fee a new transaction must pay to be admitted: 0 while there is
+    /// room, and one more than the lowest fee of the transactions that can be evicted once
+    /// the mempool is full.
+    pub fn minimum_fee(&self) -> u64 {
+        if self.transactions.len() < self.max_size {
+            return 0;
+        }
+        self.cheapest_evictable(None)
+            .map_or(u64::MAX, |(_, fee)| fee.saturating_add(1))
+    }
+
+    /// Adds a transaction to the mempool.
+    /// - Rejects transactions that are already pending.
+    /// - If the mempool is full, evicts the transaction paying the lowest fee (the latest
+    ///   one on ties) if the new transaction pays more, and rejects it otherwise. Only the
+    ///   last pending nonce of a sender can be evicted, and not one the new transaction
+    ///   follows, so no transaction loses a nonce it depends on.
+    pub fn insert(&mut self, transaction: Transaction) -> Result<(), TransactionError> {
+        let id = transaction.id();
+        if self.admitted.contains_key(&id) {
+            return Err(TransactionError::AlreadyInMempool);
+        }
+        if self.transactions.len() >= self.max_size {
+            match self.cheapest_evictable(Some(&transaction)) {
+                Some((position, fee)) if transaction.fee > fee => {
+                    let evicted = self.transactions.remove(position);
+                    self.admitted.remove(&evicted.id());
+                }
+                cheapest => {
+                    return Err(TransactionError::MempoolFull {
+                        minimum_fee: cheapest.map_or(u64::MAX, |(_, fee)| fee.saturating_add(1)),
+                    });
+                }
+            }
+        }
+
+        self.admitted.insert(id, Utc::now());
+        self.transactions.push(transaction);
+        Ok(())
+    }
+
+    /// Returns the position and fee of the transaction to evict for the incoming one: the
+    /// latest of the cheapest transactions that are the last pending nonce of their sender,
+    /// leaving out those the incoming transaction may depend on.
+    fn cheapest_evictable(&self, incoming: Option<&Transaction>) -> Option<(usize, u64)> {
+        let mut last_nonces: HashMap<&str, u64> = HashMap::new();
+        for pending in &self.transactions {
+            let last_nonce = last_nonces.entry(&pending.sender).or_insert(pending.nonce);
+            *last_nonce = (*last_nonce).max(pending.nonce);
+        }
+        self.transactions
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, pending)| last_nonces[pending.sender.as_str()] == pending.nonce)
+            .filter(|(_, pending)| {
+                incoming.is_none_or(|incoming| {
+                    incoming.sender != pending.sender || incoming.nonce < pending.nonce
+                })
+            })
+            .min_by_key(|(_, pending)| pending.fee)
+            .map(|(position, pending)| (position, pending.fee))
+    }
+
+    /// Removes the transaction with the given id and returns it.
+    pub fn remove(&mut self, id: &str) -> Option<Transaction> {
+        self.admitted.remove(id)?;
+        let position = self
+            .transactions
+            .iter()
+            .position(|transaction| transaction.id() == id)?;
+        Some(self.transactions.remove(position))
+    }
+
+    /// Removes the pending transactions that are part of the given transactions, for example
+    /// the transactions of a block added to the chain.
+    pub fn remove_included(&mut self, transactions: &[Transaction]) {
+        for transaction in transactions {
+            self.remove(&transaction.id());
+        }
+    }
+
+    /// Removes the transactions that were admitted more than `ttl` seconds before `now` and
+    /// returns them.
+    pub fn remove_stale(&mut self, now: DateTime<Utc>) -> Vec<Transaction> {
+        if self.ttl == 0 {
+            return vec![];
+        }
+        let oldest_admission = now - TimeDelta::seconds(self.ttl as i64);
+        let (stale, fresh): (Vec<Transaction>, Vec<Transaction>) =
+            std::mem::take(&mut self.transactions)
+                .into_iter()
+                .partition(|transaction| self.admitted[&transaction.id()] < oldest_admission);
+        for transaction in &stale {
+            self.admitted.remove(&transaction.id());
+        }
+        self.transactions = fresh;
+        stale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(fee: u64, nonce: u64) -> Transaction {
+        transaction_from("Alice", fee, nonce)
+    }
+
+    fn transaction_from(sender: &str, fee: u64, nonce: u64) -> Transaction {
+        Transaction::new(sender.to_string(), "Bob".to_string(), 10, fee, nonce)
+    }
+    #[test]
+    fn duplicate_transactions_are_rejected() {
+        let mut mempool = Mempool::new(10, 0);
+        assert_eq!(
+            mempool.minimum_fee(),
+            0,
+            "A mempool with room admits any fee"
+        );
+
+        assert_eq!(mempool.insert(transaction(1, 0)), Ok(()));
+        assert_eq!(
+            mempool.insert(transaction(1, 0)),
+            Err(TransactionError::AlreadyInMempool),
+            "The same transaction should only be pending once"
+        );
+        assert_eq!(mempool.len(), 1);
+        assert!(mempool.contains(&transaction(1, 0).id()));
+        assert_eq!(
+            mempool.get(&transaction(1, 0).id()),
+            Some(&transaction(1, 0))
+        );
+    }
+    #[test]
+    fn full_mempool_evicts_the_lowest_fee() {
+        // Arrange
+        let mut mempool = Mempool::new(3, 0);
+        for (sender, fee) in [("Alice", 5), ("Carol", 2), ("Dave", 2)] {
+            mempool.insert(transaction_from(sender, fee, 0)).unwrap();
+        }
+
+        // Act & Assert
+        assert_eq!(mempool.minimum_fee(), 3);
+        assert_eq!(
+            mempool.insert(transaction_from("Erin", 2, 0)),
+            Err(TransactionError::MempoolFull { minimum_fee: 3 }),
+            "Transactions not paying more than the lowest fee should be rejected"
+        );
+        assert_eq!(mempool.insert(transaction_from("Erin", 3, 0)), Ok(()));
+        assert_eq!(
+            mempool.transactions(),
+            &[
+                transaction_from("Alice", 5, 0),
+                transaction_from("Carol", 2, 0),
+                transaction_from("Erin", 3, 0)
+            ],
+            "The latest of the cheapest transactions should be evicted"
+        );
+        assert!(!mempool.contains(&transaction_from("Dave", 2, 0).id()));
+    }
+    #[test]
+    fn eviction_spares_transactions_that_later_nonces_depend_on() {
+        // Arrange: Alice's cheap first transaction is needed by her second one
+        let mut mempool = Mempool::new(3, 0);
+        mempool.insert(transaction(1, 0)).unwrap();
+        mempool.insert(transaction(5, 1)).unwrap();
+        mempool.insert(transaction_from("Carol", 3, 0)).unwrap();
+
+        // Act & Assert
+        assert_eq!(
+            mempool.minimum_fee(),
+            4,
+            "Only the last nonce of a sender should be evictable"
+        );
+        assert_eq!(mempool.insert(transaction_from("Dave", 4, 0)), Ok(()));
+        assert_eq!(
+            mempool.transactions(),
+            &[
+                transaction(1, 0),
+                transaction(5, 1),
+                transaction_from("Dave", 4, 0)
+            ]
+        );
+        assert_eq!(
+            mempool.insert(transaction(6, 2)),
+            Ok(()),
+            "The new transaction should not evict a nonce it depends on"
+        );
+        assert_eq!(
+            mempool.transactions(),
+            &[transaction(1, 0), transaction(5, 1), transaction(6, 2)]
+        );
+    }
+    #[test]
+    fn included_transactions_are_removed() {
+        let mut mempool = Mempool::new(10, 0);
+        mempool.insert(transaction(1, 0)).unwrap();
+        mempool.insert(transaction(1, 1)).unwrap();
+
+        mempool.remove_included(&[transaction(1, 0), transaction(1, 2)]);
+
+        assert_eq!(mempool.transactions(), &[transaction(1, 1)]);
+        assert_eq!(mempool.remove(&transaction(1, 0).id()), None);
+        assert_eq!(mempool.insert(transaction(1, 0)), Ok(()));
+    }
+    #[test]
+    fn stale_transactions_are_removed() {
+        let mut mempool = Mempool::new(10, 60);
+        mempool.insert(transaction(1, 0)).unwrap();
+
+        assert_eq!(mempool.remove_stale(Utc::now()), vec![]);
+        assert_eq!(
+            mempool.remove_stale(Utc::now() + TimeDelta::seconds(61)),
+            vec![transaction(1, 0)],
+            "Transactions waiting longer than the TTL should be removed"
+        );
+        assert!(mempool.is_empty());
+        assert!(!mempool.contains(&transaction(1, 0).id()));
+
+        let mut unlimited = Mempool::new(10, 0);
+        unlimited.insert(transaction(1, 0)).unwrap();
+        assert_eq!(
+            unlimited.remove_stale(Utc::now() + TimeDelta::days(365)),
+            vec![]
+        );
+    }
+}
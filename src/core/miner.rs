@@ -0,0 +1,342 @@
+use crate::common::target::compact_to_target;
+use crate::core::block::Block;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Number of hashes a worker computes before adding them to the shared counter.
+const HASH_BATCH: u64 = 256;
+
+/// Callback receiving the progress of mining jobs.
+pub type ProgressCallback = Box<dyn Fn(&MiningProgress) + Send + Sync>;
+
+/// Progress of a mining job, reported to the progress callback of a `Miner`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MiningProgress {
+    pub hashes: u64,
+    pub elapsed: Duration,
+}
+
+impl MiningProgress {
+    /// Returns the number of hashes computed per second since the job started.
+    pub fn hashrate(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.hashes as f64 / seconds
+    }
+}
+
+/// Cancels a mining job of the `Miner` it was taken from, from any thread.
+/// The handle belongs to the job running when it was taken, or to the next job if the
+/// miner was idle; later jobs need a new handle.
+#[derive(Debug, Clone)]
+pub struct CancelHandle {
+    job: u64,
+    cancelled_job: Arc<AtomicU64>,
+}
+
+impl CancelHandle {
+    /// Stops the handle's job, or keeps it from running if it has not started yet.
+    /// Has no effect once the job is over.
+    pub fn cancel(&self) {
+        self.cancelled_job.fetch_max(self.job, Ordering::Relaxed);
+    }
+}
+
+/// Mines blocks on a number of worker threads.
+/// - Every worker tries its own share of the nonces, so no nonce is hashed twice.
+/// - Once the nonces up to `max_nonce` are exhausted, the timestamp is refreshed and the
+///   nonces are tried again.
+/// - Every `progress_interval`, the progress callback receives the hashes computed so far.
+pub struct Miner {
+    pub threads: usize,
+    pub max_nonce: u64, // Last nonce tried before the timestamp is refreshed
+    pub progress_interval: Duration,
+    progress: Option<ProgressCallback>,
+    started_jobs: AtomicU64,
+    finished_jobs: AtomicU64,
+    cancelled_job: Arc<AtomicU64>, // Latest job cancelled, jobs being numbered from 1
+}
+
+impl Miner {
+    /// Creates a miner with the given number of worker threads (at least one).
+    pub fn new(threads: usize) -> Self {
+        Miner {
+            threads: threads.max(1),
+            max_nonce: u64::MAX,
+            progress_interval: Duration::from_secs(1),
+            progress: None,
+            started_jobs: AtomicU64::new(0),
+            finished_jobs: AtomicU64::new(0),
+            cancelled_job: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Sets the callback that receives the progress of mining jobs.
+    pub fn on_progress(&mut self, callback: impl Fn(&MiningProgress) + Send + Sync + 'static) {
+        self.progress = Some(Box::new(callback));
+    }
+
+    /// Returns a handle to cancel the running mining job, or the next one if the miner is
+    /// idle, from another thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            job: self.finished_jobs.load(Ordering::Relaxed) + 1,
+            cancelled_job: Arc::clone(&self.cancelled_job),
+        }
+    }
+
+    /// Mines the block by searching a nonce whose hash meets the target in the bits.
+    /// Returns `None` if the job is cancelled through a handle taken for it (see
+    /// `cancel_handle`).
+    /// Panics if the bits do not encode a valid target.
+    pub fn mine(&self, mut block: Block) -> Option<Block> {
+        assert!(
+            compact_to_target(block.bits).is_some(),
+            "Bits {:#010x} do not encode a valid target",
+            block.bits
+        );
+        let job = self.started_jobs.fetch_add(1, Ordering::Relaxed) + 1;
+        let started = Instant::now();
+        let hashes = AtomicU64::new(0);
+
+        let nonce = loop {
+            if let Some(nonce) = self.search_nonces(job, &block, &hashes, started) {
+                break Some(nonce);
+            }
+            if self.is_cancelled(job) {
+                break None;
+            }
+            block.timestamp = refreshed_timestamp(&block.timestamp);
+        };
+        self.finished_jobs.store(job, Ordering::Relaxed);
+
+        block.nonce = nonce?;
+        block.hash = block.calculate_hash();
+        Some(block)
+    }
+
+    /// Searches the nonces up to `max_nonce` for the block's timestamp, worker `i` trying
+    /// the nonces `i`, `i + threads`, `i + 2 * threads` and so on.
+    /// Returns `None` if no nonce meets the target or the job is cancelled.
+    fn search_nonces(
+        &self,
+        job: u64,
+        block: &Block,
+        hashes: &AtomicU64,
+        started: Instant,
+    ) -> Option<u64> {
+        let found = AtomicBool::new(false);
+        let stride = self.threads as u64;
+        let (sender, receiver) = mpsc::channel::<Option<u64>>();
+
+        thread::scope(|scope| {
+            for first_nonce in 0..stride {
+                let sender = sender.clone();
+                let found = &found;
+                scope.spawn(move || {
+                    let mut candidate = block.clone();
+                    let mut nonce = Some(first_nonce);
+                    let mut batch: u64 = 0;
+                    let mut result = None;
+                    while let Some(current) = nonce.filter(|nonce| *nonce <= self.max_nonce) {
+                        if found.load(Ordering::Relaxed) || self.is_cancelled(job) {
+                            break;
+                        }
+                        candidate.nonce = current;
+                        candidate.hash = candidate.calculate_hash();
+                        batch += 1;
+                        if batch == HASH_BATCH {
+                            hashes.fetch_add(batch, Ordering::Relaxed);
+                            batch = 0;
+                        }
+                        if candidate.meets_target() {
+                            found.store(true, Ordering::Relaxed);
+                            result = Some(current);
+                            break;
+                        }
+                        nonce = current.checked_add(stride);
+                    }
+                    hashes.fetch_add(batch, Ordering::Relaxed);
+                    let _ = sender.send(result);
+                });
+            }
+            drop(sender);
+
+            // Wait for a worker to find a nonce, reporting progress in between
+            loop {
+                match receiver.recv_timeout(self.progress_interval) {
+                    Ok(Some(nonce)) => return Some(nonce),
+                    Ok(None) => continue,
+                    Err(RecvTimeoutError::Timeout) => self.report_progress(hashes, started),
+                    Err(RecvTimeoutError::Disconnected) => return None,
+                }
+            }
+        })
+    }
+
+    fn is_cancelled(&self, job: u64) -> bool {
+        self.cancelled_job.load(Ordering::Relaxed) >= job
+    }
+
+    fn report_progress(&self, hashes: &AtomicU64, started: Instant) {
+        if let Some(callback) = &self.progress {
+            callback(&MiningProgress {
+                hashes: hashes.load(Ordering::Relaxed),
+                elapsed: started.elapsed(),
+            });
+        }
+    }
+}
+
+impl Default for Miner {
+    /// Creates a miner with one worker thread per available CPU.
+    fn default() -> Self {
+        Miner::new(thread::available_parallelism().map_or(1, |threads| threads.get()))
+    }
+}
+
+impl std::fmt::Debug for Miner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Miner")
+            .field("threads", &self.threads)
+            .field("max_nonce", &self.max_nonce)
+            .field("progress_interval", &self.progress_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the current time as an RFC 3339 timestamp, at least one microsecond after the
+/// given timestamp, so a refreshed block never repeats the hashes already tried.
+fn refreshed_timestamp(timestamp: &str) -> String {
+    let now = Utc::now();
+    match DateTime::parse_from_rfc3339(timestamp) {
+        Ok(previous) if now <= previous => {
+            (previous.with_timezone(&Utc) + TimeDelta::microseconds(1)).to_rfc3339()
+        }
+        _ => now.to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EASY_BITS;
+    use std::sync::Mutex;
+
+    fn template(bits: u32) -> Block {
+        Block::template(1, vec![], "GENESIS".to_string(), "Miner".to_string(), bits)
+    }
+    #[test]
+    fn workers_find_a_nonce_meeting_the_target() {
+        for threads in [1, 4] {
+            let block = Miner::new(threads).mine(template(0x2000_ffff)).unwrap();
+
+            assert!(block.meets_target(), "Block should meet its target");
+            assert_eq!(block.hash, block.calculate_hash(), "Hash covers the nonce");
+        }
+    }
+    #[test]
+    fn exhausted_nonces_refresh_the_timestamp() {
+        // Arrange: Only nonce 0 may be tried, which rarely meets a target of 1 in 256
+        let mut miner = Miner::new(2);
+        miner.max_nonce = 0;
+        let block = template(0x2000_ffff);
+
+        // Act
+        let mined = miner.mine(block.clone()).unwrap();
+
+        // Assert
+        assert_eq!(mined.nonce, 0, "Nonce should stay within the limit");
+        assert!(mined.meets_target());
+        if block.meets_target() {
+            assert_eq!(mined.timestamp, block.timestamp);
+        } else {
+            assert!(
+                mined.time() > block.time(),
+                "Timestamp should be refreshed when the nonces are exhausted"
+            );
+        }
+    }
+    #[test]
+    fn cancelled_job_returns_no_block() {
+        // Arrange: Cancel from another thread while mining an unreachable target
+        let mut miner = Miner::new(2);
+        miner.progress_interval = Duration::from_millis(5);
+        let handle = miner.cancel_handle();
+        let stale_handle = handle.clone();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let progress = Arc::clone(&reports);
+        miner.on_progress(move |report| progress.lock().unwrap().push(*report));
+
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            handle.cancel();
+        });
+
+        // Act
+        let result = miner.mine(template(0x0300_0001));
+        canceller.join().unwrap();
+
+        // Assert
+        assert_eq!(result, None, "Cancelled job should not return a block");
+        let reports = reports.lock().unwrap();
+        assert!(!reports.is_empty(), "Progress should be reported");
+        assert!(
+            reports
+                .windows(2)
+                .all(|pair| pair[0].hashes <= pair[1].hashes),
+            "Hash count should only grow"
+        );
+
+        // The cancellation does not affect the next job, nor does a handle of a finished job
+        assert!(miner.mine(template(EASY_BITS)).is_some());
+        stale_handle.cancel();
+        assert!(
+            miner.mine(template(EASY_BITS)).is_some(),
+            "Handle of a finished job should not cancel later jobs"
+        );
+    }
+    #[test]
+    fn cancellation_before_the_job_starts_is_kept() {
+        // Arrange
+        let miner = Miner::new(1);
+        let handle = miner.cancel_handle();
+
+        // Act
+        handle.cancel();
+        let result = miner.mine(template(EASY_BITS));
+
+        // Assert
+        assert_eq!(
+            result, None,
+            "Job cancelled while the miner was idle should not run"
+        );
+        assert!(
+            miner.mine(template(EASY_BITS)).is_some(),
+            "Cancellation should only apply to the job it was taken for"
+        );
+    }
+    #[test]
+    fn hashrate_divides_hashes_by_elapsed_time() {
+        let progress = MiningProgress {
+            hashes: 3_000,
+            elapsed: Duration::from_millis(1_500),
+        };
+
+        assert_eq!(progress.hashrate(), 2_000.0);
+        assert_eq!(
+            MiningProgress {
+                hashes: 10,
+                elapsed: Duration::ZERO
+            }
+            .hashrate(),
+            0.0
+        );
+    }
+}
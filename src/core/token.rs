@@ -5,7 +5,7 @@ pub struct Token {
     pub symbol: String,
     pub decimals: u8,
     pub smallest_unit: u64,
-    pub total_supply: u64,
+    pub total_supply: u64, // In smallest units, like every amount
 }
 
 impl Token {
@@ -0,0 +1,490 @@
+use crate::common::encoding::{Decoder, Encoder};
+use crate::errors::encoding_errors::DecodeError;
+use secp256k1::ecdsa::Signature;
+use secp256k1::{Message, PublicKey, Secp256k1};
+use sha2::{Digest, Sha256};
+
+/// Represents a blockchain transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub sender: String,                // Address of the sender
+    pub receiver: String,              // Address of the receiver
+    pub amount: u64,                   // Amount to be transferred (in smallest units)
+    pub fee: u64,                      // Fee paid to the block beneficiary (in smallest units)
+    pub nonce: u64,                    // Sequence number within the sender's account
+    pub valid_until: Option<u32>,      // Last block height the transaction may be included at
+    pub signature: Option<Signature>,  // ECDSA signature of the sender over the signing payload
+    pub public_key: Option<PublicKey>, // Public key of the sender, used to verify the signature
+}
+
+impl Transaction {
+    /// Creates a new transaction with the specified sender, receiver, amount, fee, and nonce.
+    /// - The sender pays the fee on top of the amount.
+    /// - The nonce must equal the number of transactions the sender has already confirmed.
+    /// - The transaction is unsigned until `sign` is called.
+    pub fn new(sender: String, receiver: String, amount: u64, fee: u64, nonce: u64) -> Self {
+        Transaction {
+            sender,
+            receiver,
+            amount,
+            fee,
+            nonce,
+            valid_until: None,
+            signature: None,
+            public_key: None,
+        }
+    }
+
+    /// Limits the transaction to blocks up to the given height.
+    /// Must be set before signing, as the signature covers it.
+    pub fn with_valid_until(mut self, height: u32) -> Self {
+        self.valid_until = Some(height);
+        self
+    }
+
+    /// Returns `true` if the transaction may no longer be included in a block at the given
+    /// height.
+    pub fn is_expired_at(&self, height: u32) -> bool {
+        self.valid_until
+            .is_some_and(|valid_until| height > valid_until)
+    }
+
+    /// Creates the coinbase transaction of the block at the given height.
+    /// - Mints the amount to the receiver; it has no sender, fee, or signature.
+    /// - Uses the block height as nonce, so coinbase transactions of different blocks differ.
+    pub fn coinbase(receiver: String, amount: u64, height: u32) -> Self {
+        Transaction::new(String::new(), receiver, amount, 0, height as u64)
+    }
+
+    /// Returns `true` if the transaction mints new coins instead of spending them.
+    pub fn is_coinbase(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    /// Returns the canonical encoding of the data covered by the sender's signature.
+    /// Excludes the signature and public key, which are attached after signing.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        self.encode_data(&mut encoder);
+        encoder.finish()
+    }
+
+    /// Returns the canonical encoding of the transaction, including signature and public key.
+    /// The signature is written in its 64-byte compact form.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        self.encode_data(&mut encoder);
+        encoder.put_presence(self.signature.is_some());
+        if let Some(signature) = &self.signature {
+            encoder.put_bytes(&signature.serialize_compact());
+        }
+        encoder.put_presence(self.public_key.is_some());
+        if let Some(public_key) = &self.public_key {
+            encoder.put_bytes(&public_key.serialize());
+        }
+        encoder.finish()
+    }
+
+    /// Decodes a transaction from its canonical encoding.
+    /// - Signatures must use the compact form.
+    /// - Public keys must use the compressed form, so every transaction has one encoding.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut decoder = Decoder::new(bytes)?;
+        let mut transaction = Transaction::new(
+            decoder.get_str()?,
+            decoder.get_str()?,
+            decoder.get_u64()?,
+            decoder.get_u64()?,
+            decoder.get_u64()?,
+        );
+        if decoder.get_presence()? {
+            transaction.valid_until = Some(decoder.get_u32()?);
+        }
+        if decoder.get_presence()? {
+            let signature = Signature::from_compact(decoder.get_bytes()?)
+                .map_err(|_| DecodeError::InvalidSignature)?;
+            transaction.signature = Some(signature);
+        }
+        if decoder.get_presence()? {
+            let public_key_bytes = decoder.get_bytes()?;
+            if public_key_bytes.len() != secp256k1::constants::PUBLIC_KEY_SIZE {
+                return Err(DecodeError::InvalidPublicKey);
+            }
+            let public_key = PublicKey::from_slice(public_key_bytes)
+                .map_err(|_| DecodeError::InvalidPublicKey)?;
+            transaction.public_key = Some(public_key);
+        }
+        decoder.finish()?;
+        Ok(transaction)
+    }
+
+    /// Writes the fields covered by the signature.
+    fn encode_data(&self, encoder: &mut Encoder) {
+        encoder.put_str(&self.sender);
+        encoder.put_str(&self.receiver);
+        encoder.put_u64(self.amount);
+        encoder.put_u64(self.fee);
+        encoder.put_u64(self.nonce);
+        encoder.put_presence(self.valid_until.is_some());
+        if let Some(valid_until) = self.valid_until {
+            encoder.put_u32(valid_until);
+        }
+    }
+
+    /// Returns the transaction id: the hash of the canonical encoding of the signed data.
+    /// Unlike `hash`, it stays the same when the transaction is signed, so it refers to the
+    /// transfer the sender authorized however its signature is encoded.
+    pub fn id(&self) -> String {
+        hex::encode(Sha256::digest(self.signing_payload()))
+    }
+
+    /// Calculates the hash of the canonical encoding, including signature and public key.
+    /// Used as the leaf of the block's Merkle tree.
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.encode()))
+    }
+
+    /// Attaches the sender's signature and public key to the transaction.
+    pub fn sign(&mut self, signature: Signature, public_key: PublicKey) {
+        self.signature = Some(signature);
+        self.public_key = Some(public_key);
+    }
+
+    /// Verifies the transaction signature against the given public key.
+    /// - Returns `false` when the transaction is unsigned.
+    pub fn verify(&self, public_key: &PublicKey) -> bool {
+        let Some(signature) = &self.signature else {
+            return false;
+        };
+        let secp = Secp256k1::verification_only();
+        let message_hash = Sha256::digest(self.signing_payload());
+        let message = Message::from_digest(message_hash.0);
+        secp.verify_ecdsa(&message, signature, public_key).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::wallet::Wallet;
+    #[test]
+    fn create_new_transaction() {
+        // Arrange: Set up sender, receiver, and amount
+        let sender = "Alice".to_string();
+        let receiver = "Bob".to_string();
+        let amount = 100;
+        let fee = 2;
+        let nonce = 7;
+
+        let transaction = Transaction::new(sender.clone(), receiver.clone(), amount, fee, nonce);
+
+        assert_eq!(
+            transaction.sender, sender,
+            "Sender should match the provided value"
+        );
+        assert_eq!(
+            transaction.receiver, receiver,
+            "Receiver should match the provided value"
+        );
+        assert_eq!(
+            transaction.amount, amount,
+            "Amount should match the provided value"
+        );
+        assert_eq!(transaction.fee, fee, "Fee should match the provided value");
+        assert_eq!(
+            transaction.nonce, nonce,
+            "Nonce should match the provided value"
+        );
+        assert_eq!(
+            transaction.signature, None,
+            "A new transaction should not be signed"
+        );
+        assert_eq!(
+            transaction.public_key, None,
+            "A new transaction should not carry a public key"
+        );
+    }
+    #[test]
+    fn transaction_encoding_round_trips() {
+        let wallet = Wallet::new();
+        let unsigned = Transaction::new(wallet.get_address(), "Bob".to_string(), 100, 2, 3);
+        let mut signed = unsigned.clone();
+        signed.sign(
+            wallet.sign_transaction(&signed.signing_payload()),
+            wallet.public_key,
+        );
+        let coinbase = Transaction::coinbase("Miner".to_string(), 5_000, 1);
+        let expiring =
+            Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 2, 3).with_valid_until(7);
+
+        for transaction in [unsigned, signed, coinbase, expiring] {
+            assert_eq!(
+                Transaction::decode(&transaction.encode()),
+                Ok(transaction.clone()),
+                "Decoding the encoding should return the original transaction"
+            );
+        }
+    }
+    #[test]
+    fn transaction_encoding_has_no_field_collisions() {
+        // All of these stringify to "AliceBob10023" when fields are simply concatenated
+        let transactions = [
+            Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 2, 3),
+            Transaction::new("AliceB".to_string(), "ob".to_string(), 100, 2, 3),
+            Transaction::new("Alice".to_string(), "Bob1".to_string(), 0, 0, 23),
+            Transaction::new("Alice".to_string(), "Bob".to_string(), 10, 0, 23),
+        ];
+
+        for (i, first) in transactions.iter().enumerate() {
+            for second in &transactions[i + 1..] {
+                assert_ne!(
+                    first.signing_payload(),
+                    second.signing_payload(),
+                    "Different transactions should have different signing payloads"
+                );
+                assert_ne!(
+                    first.hash(),
+                    second.hash(),
+                    "Different transactions should have different hashes"
+                );
+            }
+        }
+    }
+    #[test]
+    fn signing_payload_excludes_signature() {
+        let wallet = Wallet::new();
+        let mut transaction = Transaction::new(wallet.get_address(), "Bob".to_string(), 100, 0, 0);
+        let payload = transaction.signing_payload();
+
+        transaction.sign(wallet.sign_transaction(&payload), wallet.public_key);
+
+        assert_eq!(
+            transaction.signing_payload(),
+            payload,
+            "Signing should not change the signing payload"
+        );
+        assert_ne!(
+            transaction.encode(),
+            payload,
+            "The full encoding should include the signature"
+        );
+    }
+    #[test]
+    fn signature_covers_the_expiry() {
+        let wallet = Wallet::new();
+        let mut transaction = Transaction::new(wallet.get_address(), "Bob".to_string(), 100, 0, 0)
+            .with_valid_until(10);
+        transaction.sign(
+            wallet.sign_transaction(&transaction.signing_payload()),
+            wallet.public_key,
+        );
+        assert!(transaction.verify(&wallet.public_key));
+
+        assert!(!transaction.is_expired_at(10), "Valid up to its height");
+        assert!(transaction.is_expired_at(11));
+        assert!(!Transaction::coinbase("Miner".to_string(), 1, 1).is_expired_at(u32::MAX));
+
+        transaction.valid_until = Some(20);
+        assert!(
+            !transaction.verify(&wallet.public_key),
+            "Extending the expiry should invalidate the signature"
+        );
+    }
+    #[test]
+    fn malformed_transaction_encoding_is_rejected() {
+        let wallet = Wallet::new();
+        let mut transaction = Transaction::new(wallet.get_address(), "Bob".to_string(), 100, 0, 0);
+        transaction.sign(
+            wallet.sign_transaction(&transaction.signing_payload()),
+            wallet.public_key,
+        );
+        let bytes = transaction.encode();
+
+        // Truncated input
+        assert_eq!(
+            Transaction::decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd),
+            "Truncated encoding should be rejected"
+        );
+
+        // Trailing input
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            Transaction::decode(&extended),
+            Err(DecodeError::TrailingBytes { count: 1 }),
+            "Encoding with trailing bytes should be rejected"
+        );
+
+        // Signature not in the compact form
+        let mut encoder = Encoder::new();
+        transaction.encode_data(&mut encoder);
+        encoder.put_presence(true);
+        encoder.put_bytes(&transaction.signature.unwrap().serialize_der());
+        encoder.put_presence(false);
+        assert_eq!(
+            Transaction::decode(&encoder.finish()),
+            Err(DecodeError::InvalidSignature),
+            "DER signatures should be rejected"
+        );
+
+        // Uncompressed public key
+        let mut encoder = Encoder::new();
+        transaction.encode_data(&mut encoder);
+        encoder.put_presence(false);
+        encoder.put_presence(true);
+        encoder.put_bytes(&wallet.public_key.serialize_uncompressed());
+        assert_eq!(
+            Transaction::decode(&encoder.finish()),
+            Err(DecodeError::InvalidPublicKey),
+            "Uncompressed public keys should be rejected"
+        );
+    }
+    #[test]
+    fn create_coinbase_transaction() {
+        let coinbase = Transaction::coinbase("Miner".to_string(), 5_000, 12);
+
+        assert!(coinbase.is_coinbase(), "Coinbase should be recognized");
+        assert_eq!(coinbase.sender, "", "Coinbase should have no sender");
+        assert_eq!(coinbase.receiver, "Miner", "Receiver should match");
+        assert_eq!(coinbase.amount, 5_000, "Amount should match");
+        assert_eq!(coinbase.fee, 0, "Coinbase should pay no fee");
+        assert_eq!(coinbase.nonce, 12, "Nonce should equal the block height");
+        assert_eq!(coinbase.signature, None, "Coinbase should not be signed");
+
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 0, 0);
+        assert!(
+            !transaction.is_coinbase(),
+            "Regular transaction should not be recognized as coinbase"
+        );
+    }
+    #[test]
+    fn transaction_hash_covers_data_and_signature() {
+        let wallet = Wallet::new();
+        let mut transaction = Transaction::new(wallet.get_address(), "Bob".to_string(), 100, 0, 0);
+        let unsigned_hash = transaction.hash();
+
+        assert_eq!(
+            unsigned_hash.len(),
+            64,
+            "Hash should be a SHA256 hex string"
+        );
+        assert_eq!(
+            unsigned_hash,
+            transaction.clone().hash(),
+            "Hash should be deterministic"
+        );
+
+        let signature = wallet.sign_transaction(&transaction.signing_payload());
+        transaction.sign(signature, wallet.public_key);
+        assert_ne!(
+            transaction.hash(),
+            unsigned_hash,
+            "Hash should change when the signature changes"
+        );
+
+        let mut modified = transaction.clone();
+        modified.amount = 101;
+        assert_ne!(
+            modified.hash(),
+            transaction.hash(),
+            "Hash should change when the data changes"
+        );
+    }
+    #[test]
+    fn transaction_id_covers_data_but_not_signature() {
+        let wallet = Wallet::new();
+        let mut transaction = Transaction::new(wallet.get_address(), "Bob".to_string(), 100, 0, 0);
+        let unsigned_id = transaction.id();
+
+        transaction.sign(
+            wallet.sign_transaction(&transaction.signing_payload()),
+            wallet.public_key,
+        );
+        assert_eq!(
+            transaction.id(),
+            unsigned_id,
+            "Signing should not change the id"
+        );
+        assert_ne!(transaction.id(), transaction.hash());
+
+        let mut modified = transaction.clone();
+        modified.nonce = 1;
+        assert_ne!(
+            modified.id(),
+            unsigned_id,
+            "Id should change when the data changes"
+        );
+    }
+    #[test]
+    fn signed_transaction_verifies() {
+        let wallet = Wallet::new();
+        let mut transaction = Transaction::new(wallet.get_address(), "Bob".to_string(), 100, 0, 0);
+
+        let signature = wallet.sign_transaction(&transaction.signing_payload());
+        transaction.sign(signature, wallet.public_key);
+
+        assert_eq!(
+            transaction.public_key,
+            Some(wallet.public_key),
+            "Signing should attach the public key"
+        );
+        assert!(
+            transaction.verify(&wallet.public_key),
+            "Signature should verify against the signer's public key"
+        );
+    }
+    #[test]
+    fn verification_fails_for_wrong_key_tampering_or_missing_signature() {
+        let wallet = Wallet::new();
+        let other_wallet = Wallet::new();
+        let mut transaction = Transaction::new(wallet.get_address(), "Bob".to_string(), 100, 0, 0);
+
+        // Unsigned transaction
+        assert!(
+            !transaction.verify(&wallet.public_key),
+            "Unsigned transaction should not verify"
+        );
+
+        let signature = wallet.sign_transaction(&transaction.signing_payload());
+        transaction.sign(signature, wallet.public_key);
+
+        // Wrong public key
+        assert!(
+            !transaction.verify(&other_wallet.public_key),
+            "Signature should not verify against another public key"
+        );
+
+        // Tampered amount
+        let mut tampered = transaction.clone();
+        tampered.amount = 1_000;
+        assert!(
+            !tampered.verify(&wallet.public_key),
+            "Signature should not verify after the transaction is modified"
+        );
+
+        // Signature of other data
+        let mut misplaced = transaction.clone();
+        misplaced.signature = Some(wallet.sign_transaction(b"other data"));
+        assert!(
+            !misplaced.verify(&wallet.public_key),
+            "Signature over other data should not verify"
+        );
+
+        // Modified fee
+        let mut repriced = transaction.clone();
+        repriced.fee = 1;
+        assert!(
+            !repriced.verify(&wallet.public_key),
+            "Signature should not verify after the fee is modified"
+        );
+
+        // Replayed with another nonce
+        let mut replayed = transaction.clone();
+        replayed.nonce = 1;
+        assert!(
+            !replayed.verify(&wallet.public_key),
+            "Signature should not verify after the nonce is modified"
+        );
+    }
+}
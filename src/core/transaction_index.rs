@@ -0,0 +1,113 @@
+use crate::core::block::Block;
+use crate::core::transaction::Transaction;
+use std::collections::HashMap;
+
+/// Where a transaction is included in the main chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionLocation {
+    pub height: u32,   // Height of the block holding the transaction
+    pub position: u32, // Position within the block's transactions, the coinbase being 0
+}
+
+/// A transaction looked up by its id, see `Blockchain::get_transaction`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedTransaction {
+    pub transaction: Transaction,
+    pub location: Option<TransactionLocation>, // `None` while the transaction is pending
+    pub confirmations: u32, // Number of blocks from the transaction's block to the tip
+}
+
+/// Locations of the transactions of the main chain by transaction id.
+/// Blocks are added when they are connected and removed when they are disconnected.
+#[derive(Debug, Default)]
+pub struct TransactionIndex {
+    locations: HashMap<String, TransactionLocation>,
+}
+
+impl TransactionIndex {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        TransactionIndex::default()
+    }
+
+    /// Returns the number of indexed transactions.
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Returns `true` if no transaction is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// Returns the location of the transaction with the given id.
+    pub fn get(&self, id: &str) -> Option<TransactionLocation> {
+        self.locations.get(id).copied()
+    }
+
+    /// Indexes the transactions of a block connected to the main chain.
+    pub fn insert_block(&mut self, block: &Block) {
+        for (position, transaction) in block.transactions.iter().enumerate() {
+            self.locations.insert(
+                transaction.id(),
+                TransactionLocation {
+                    height: block.index,
+                    position: position as u32,
+                },
+            );
+        }
+    }
+
+    /// Removes the transactions of a block disconnected from the main chain.
+    pub fn remove_block(&mut self, block: &Block) {
+        for transaction in &block.transactions {
+            if self
+                .get(&transaction.id())
+                .is_some_and(|location| location.height == block.index)
+            {
+                self.locations.remove(&transaction.id());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EASY_BITS;
+
+    #[test]
+    fn index_follows_connected_blocks() {
+        // Arrange
+        let transfer = Transaction::new("Alice".to_string(), "Bob".to_string(), 10, 0, 0);
+        let coinbase = Transaction::coinbase("Miner".to_string(), 50, 1);
+        let block = Block::new(
+            1,
+            vec![coinbase.clone(), transfer.clone()],
+            "GENESIS".to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+        let mut index = TransactionIndex::new();
+
+        // Act
+        index.insert_block(&block);
+
+        // Assert
+        assert_eq!(
+            index.get(&transfer.id()),
+            Some(TransactionLocation {
+                height: 1,
+                position: 1
+            })
+        );
+        assert_eq!(
+            index.get(&coinbase.id()).map(|location| location.position),
+            Some(0)
+        );
+        assert_eq!(index.len(), 2);
+
+        index.remove_block(&block);
+        assert!(index.is_empty(), "Disconnected blocks leave the index");
+    }
+}
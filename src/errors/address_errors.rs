@@ -0,0 +1,114 @@
+#[derive(Debug, PartialEq)]
+pub enum AddressError {
+    Malformed { message: String },
+    InvalidChecksum,
+    NotCanonical,
+    UnknownNetwork { prefix: String },
+    WrongNetwork { expected: String, found: String },
+    UnsupportedVersion { version: u8 },
+    InvalidLength { length: usize },
+}
+
+impl std::fmt::Display for AddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressError::Malformed { message } => {
+                write!(f, "Invalid address: {}", message)
+            }
+            AddressError::InvalidChecksum => {
+                write!(f, "Invalid address: Checksum mismatch.")
+            }
+            AddressError::NotCanonical => {
+                write!(
+                    f,
+                    "Invalid address: Addresses must be written in lowercase."
+                )
+            }
+            AddressError::UnknownNetwork { prefix } => {
+                write!(f, "Invalid address: Unknown network prefix '{}'.", prefix)
+            }
+            AddressError::WrongNetwork { expected, found } => {
+                write!(
+                    f,
+                    "Invalid address: Network prefix '{}' expected, '{}' found.",
+                    expected, found
+                )
+            }
+            AddressError::UnsupportedVersion { version } => {
+                write!(f, "Invalid address: Unsupported version {}.", version)
+            }
+            AddressError::InvalidLength { length } => {
+                write!(
+                    f,
+                    "Invalid address: A key hash has 20 bytes, not {}.",
+                    length
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn address_error_message_formatting() {
+        let error = AddressError::Malformed {
+            message: "invalid character".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Invalid address: invalid character",
+            "Display output for Malformed is incorrect"
+        );
+
+        let error = AddressError::InvalidChecksum;
+        assert_eq!(
+            format!("{}", error),
+            "Invalid address: Checksum mismatch.",
+            "Display output for InvalidChecksum is incorrect"
+        );
+
+        let error = AddressError::NotCanonical;
+        assert_eq!(
+            format!("{}", error),
+            "Invalid address: Addresses must be written in lowercase.",
+            "Display output for NotCanonical is incorrect"
+        );
+
+        let error = AddressError::UnknownNetwork {
+            prefix: "bc".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Invalid address: Unknown network prefix 'bc'.",
+            "Display output for UnknownNetwork is incorrect"
+        );
+
+        let error = AddressError::WrongNetwork {
+            expected: "tok".to_string(),
+            found: "ttok".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Invalid address: Network prefix 'tok' expected, 'ttok' found.",
+            "Display output for WrongNetwork is incorrect"
+        );
+
+        let error = AddressError::UnsupportedVersion { version: 1 };
+        assert_eq!(
+            format!("{}", error),
+            "Invalid address: Unsupported version 1.",
+            "Display output for UnsupportedVersion is incorrect"
+        );
+
+        let error = AddressError::InvalidLength { length: 32 };
+        assert_eq!(
+            format!("{}", error),
+            "Invalid address: A key hash has 20 bytes, not 32.",
+            "Display output for InvalidLength is incorrect"
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use crate::errors::address_errors::AddressError;
+use crate::errors::transaction_errors::TransactionError;
+
+#[derive(Debug, PartialEq)]
+pub enum BlockValidationError {
+    GenesisMismatch,
+    DuplicateBlock {
+        index: u32,
+    },
+    UnknownParent {
+        index: u32,
+    },
+    UnexpectedIndex {
+        expected: u32,
+        found: u32,
+    },
+    BrokenLink {
+        index: u32,
+    },
+    InvalidHash {
+        index: u32,
+    },
+    InvalidTimestamp {
+        index: u32,
+    },
+    TimestampNotAfterMedian {
+        index: u32,
+    },
+    TimestampTooFarInFuture {
+        index: u32,
+    },
+    UnexpectedTarget {
+        index: u32,
+        expected: u32,
+        found: u32,
+    },
+    TargetNotMet {
+        index: u32,
+    },
+    TooManyTransactions {
+        index: u32,
+        max: usize,
+        found: usize,
+    },
+    BlockTooLarge {
+        index: u32,
+        max: usize,
+        found: usize,
+    },
+    InvalidMerkleRoot {
+        index: u32,
+    },
+    DuplicateTransaction {
+        index: u32,
+        id: String,
+    },
+    MissingCoinbase {
+        index: u32,
+    },
+    MultipleCoinbases {
+        index: u32,
+    },
+    CoinbaseNotPaidToBeneficiary {
+        index: u32,
+    },
+    CoinbaseNonceNotHeight {
+        index: u32,
+        found: u64,
+    },
+    MalformedCoinbase {
+        index: u32,
+    },
+    InvalidBeneficiary {
+        index: u32,
+        address: String,
+        error: AddressError,
+    },
+    CoinbaseExceedsSubsidy {
+        index: u32,
+        subsidy: u64,
+        found: u64,
+    },
+    CoinbaseExceedsTotalSupply {
+        index: u32,
+    },
+    InvalidTransaction {
+        index: u32,
+        error: TransactionError,
+    },
+}
+
+impl std::fmt::Display for BlockValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockValidationError::GenesisMismatch => {
+                write!(
+                    f,
+                    "Block validation failed: Genesis block does not match the configuration."
+                )
+            }
+            BlockValidationError::DuplicateBlock { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} is already known.",
+                    index
+                )
+            }
+            BlockValidationError::UnknownParent { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} does not follow a known block.",
+                    index
+                )
+            }
+            BlockValidationError::UnexpectedIndex { expected, found } => {
+                write!(
+                    f,
+                    "Block validation failed: Unexpected index ({} expected, {} found).",
+                    expected, found
+                )
+            }
+            BlockValidationError::BrokenLink { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Chain is broken at block {}.",
+                    index
+                )
+            }
+            BlockValidationError::InvalidHash { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} has an invalid hash.",
+                    index
+                )
+            }
+            BlockValidationError::InvalidTimestamp { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} has an invalid timestamp.",
+                    index
+                )
+            }
+            BlockValidationError::TimestampNotAfterMedian { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} is not timestamped after the median time of its previous blocks.",
+                    index
+                )
+            }
+            BlockValidationError::TimestampTooFarInFuture { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} is timestamped too far in the future.",
+                    index
+                )
+            }
+            BlockValidationError::UnexpectedTarget {
+                index,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} has an unexpected target ({:#010x} expected, {:#010x} found).",
+                    index, expected, found
+                )
+            }
+            BlockValidationError::TargetNotMet { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} does not meet its target.",
+                    index
+                )
+            }
+            BlockValidationError::TooManyTransactions { index, max, found } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} has too many transactions ({} allowed, {} found).",
+                    index, max, found
+                )
+            }
+            BlockValidationError::BlockTooLarge { index, max, found } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} is too large ({} bytes allowed, {} bytes found).",
+                    index, max, found
+                )
+            }
+            BlockValidationError::InvalidMerkleRoot { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} has an invalid Merkle root.",
+                    index
+                )
+            }
+            BlockValidationError::DuplicateTransaction { index, id } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} contains transaction {} more than once.",
+                    index, id
+                )
+            }
+            BlockValidationError::MissingCoinbase { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} does not start with a coinbase transaction.",
+                    index
+                )
+            }
+            BlockValidationError::MultipleCoinbases { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} has more than one coinbase transaction.",
+                    index
+                )
+            }
+            BlockValidationError::CoinbaseNotPaidToBeneficiary { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} pays its coinbase to another address than its beneficiary.",
+                    index
+                )
+            }
+            BlockValidationError::CoinbaseNonceNotHeight { index, found } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} coinbase has nonce {} instead of the block height.",
+                    index, found
+                )
+            }
+            BlockValidationError::MalformedCoinbase { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} coinbase carries a fee, a signature or a public key.",
+                    index
+                )
+            }
+            BlockValidationError::InvalidBeneficiary {
+                index,
+                address,
+                error,
+            } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} beneficiary {} is not a valid address. {}",
+                    index, address, error
+                )
+            }
+            BlockValidationError::CoinbaseExceedsSubsidy {
+                index,
+                subsidy,
+                found,
+            } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} coinbase exceeds the block subsidy ({} allowed, {} found).",
+                    index, subsidy, found
+                )
+            }
+            BlockValidationError::CoinbaseExceedsTotalSupply { index } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} coinbase exceeds the total supply.",
+                    index
+                )
+            }
+            BlockValidationError::InvalidTransaction { index, error } => {
+                write!(
+                    f,
+                    "Block validation failed: Block {} contains an invalid transaction. {}",
+                    index, error
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BlockValidationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BlockValidationError::InvalidBeneficiary { error, .. } => Some(error),
+            BlockValidationError::InvalidTransaction { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn block_validation_error_message_formatting() {
+        let error = BlockValidationError::GenesisMismatch;
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Genesis block does not match the configuration.",
+            "Display output for GenesisMismatch is incorrect"
+        );
+
+        let error = BlockValidationError::UnknownParent { index: 3 };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 does not follow a known block.",
+            "Display output for UnknownParent is incorrect"
+        );
+
+        let error = BlockValidationError::UnexpectedIndex {
+            expected: 2,
+            found: 5,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Unexpected index (2 expected, 5 found).",
+            "Display output for UnexpectedIndex is incorrect"
+        );
+
+        let error = BlockValidationError::TimestampNotAfterMedian { index: 3 };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 is not timestamped after the median time of its previous blocks.",
+            "Display output for TimestampNotAfterMedian is incorrect"
+        );
+
+        let error = BlockValidationError::UnexpectedTarget {
+            index: 3,
+            expected: 0x1d00_ffff,
+            found: 0x207f_ffff,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 has an unexpected target (0x1d00ffff expected, 0x207fffff found).",
+            "Display output for UnexpectedTarget is incorrect"
+        );
+
+        let error = BlockValidationError::BlockTooLarge {
+            index: 3,
+            max: 1_000,
+            found: 1_001,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 is too large (1000 bytes allowed, 1001 bytes found).",
+            "Display output for BlockTooLarge is incorrect"
+        );
+
+        let error = BlockValidationError::DuplicateTransaction {
+            index: 3,
+            id: "abc".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 contains transaction abc more than once.",
+            "Display output for DuplicateTransaction is incorrect"
+        );
+
+        let error = BlockValidationError::CoinbaseExceedsSubsidy {
+            index: 3,
+            subsidy: 50,
+            found: 51,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 coinbase exceeds the block subsidy (50 allowed, 51 found).",
+            "Display output for CoinbaseExceedsSubsidy is incorrect"
+        );
+
+        let error = BlockValidationError::CoinbaseNonceNotHeight { index: 3, found: 4 };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 coinbase has nonce 4 instead of the block height.",
+            "Display output for CoinbaseNonceNotHeight is incorrect"
+        );
+
+        let error = BlockValidationError::InvalidBeneficiary {
+            index: 3,
+            address: "peer".to_string(),
+            error: AddressError::InvalidChecksum,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 beneficiary peer is not a valid address. Invalid address: Checksum mismatch.",
+            "Display output for InvalidBeneficiary is incorrect"
+        );
+
+        let error = BlockValidationError::InvalidTransaction {
+            index: 3,
+            error: TransactionError::InvalidSignature,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Block validation failed: Block 3 contains an invalid transaction. Transaction rejected: Invalid signature.",
+            "Display output for InvalidTransaction is incorrect"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::errors::block_validation_errors::BlockValidationError;
+use crate::errors::config_errors::ConfigError;
+use crate::errors::storage_errors::StorageError;
+use crate::errors::transaction_errors::TransactionError;
+
+/// Error of a blockchain operation.
+/// Wraps the error of the failing layer, or describes a failure of the operation itself.
+#[derive(Debug, PartialEq)]
+pub enum BlockchainError {
+    Config(ConfigError),
+    BlockValidation(BlockValidationError),
+    Transaction(TransactionError),
+    Storage(StorageError),
+    NoValidTransactions,
+    MiningCancelled { index: u32 },
+    MissingUndo { index: u32 },
+    GenesisCannotBeDisconnected,
+}
+
+impl std::fmt::Display for BlockchainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockchainError::Config(error) => write!(f, "{}", error),
+            BlockchainError::BlockValidation(error) => write!(f, "{}", error),
+            BlockchainError::Transaction(error) => write!(f, "{}", error),
+            BlockchainError::Storage(error) => write!(f, "{}", error),
+            BlockchainError::NoValidTransactions => {
+                write!(f, "Mining failed: No valid transactions to include.")
+            }
+            BlockchainError::MiningCancelled { index } => {
+                write!(f, "Mining failed: Mining block {} was cancelled.", index)
+            }
+            BlockchainError::MissingUndo { index } => {
+                write!(f, "Blockchain failed: Block {} has no undo record.", index)
+            }
+            BlockchainError::GenesisCannotBeDisconnected => {
+                write!(
+                    f,
+                    "Blockchain failed: The genesis block cannot be disconnected."
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BlockchainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BlockchainError::Config(error) => Some(error),
+            BlockchainError::BlockValidation(error) => Some(error),
+            BlockchainError::Transaction(error) => Some(error),
+            BlockchainError::Storage(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for BlockchainError {
+    fn from(error: ConfigError) -> Self {
+        BlockchainError::Config(error)
+    }
+}
+
+impl From<BlockValidationError> for BlockchainError {
+    fn from(error: BlockValidationError) -> Self {
+        BlockchainError::BlockValidation(error)
+    }
+}
+
+impl From<TransactionError> for BlockchainError {
+    fn from(error: TransactionError) -> Self {
+        BlockchainError::Transaction(error)
+    }
+}
+
+impl From<StorageError> for BlockchainError {
+    fn from(error: StorageError) -> Self {
+        BlockchainError::Storage(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    #[test]
+    fn blockchain_error_message_formatting() {
+        let error = BlockchainError::NoValidTransactions;
+        assert_eq!(
+            format!("{}", error),
+            "Mining failed: No valid transactions to include.",
+            "Display output for NoValidTransactions is incorrect"
+        );
+
+        let error = BlockchainError::MiningCancelled { index: 3 };
+        assert_eq!(
+            format!("{}", error),
+            "Mining failed: Mining block 3 was cancelled.",
+            "Display output for MiningCancelled is incorrect"
+        );
+
+        let error = BlockchainError::MissingUndo { index: 3 };
+        assert_eq!(
+            format!("{}", error),
+            "Blockchain failed: Block 3 has no undo record.",
+            "Display output for MissingUndo is incorrect"
+        );
+    }
+    #[test]
+    fn wrapped_errors_keep_their_message_and_source() {
+        let error: BlockchainError = TransactionError::InvalidSignature.into();
+
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Invalid signature.",
+            "Wrapped errors should display the original message"
+        );
+        assert_eq!(
+            error.source().map(|source| source.to_string()),
+            Some("Transaction rejected: Invalid signature.".to_string()),
+            "Wrapped error should be the source"
+        );
+        assert!(BlockchainError::NoValidTransactions.source().is_none());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::errors::address_errors::AddressError;
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    CurrentDirectory {
+        message: String,
+    },
+    Read {
+        path: String,
+        message: String,
+    },
+    Parse {
+        path: String,
+        message: String,
+    },
+    TotalSupplyLessThanPreMined {
+        total_supply: u64,
+        pre_mined: u64,
+    },
+    InvalidGenesisBits {
+        bits: u32,
+    },
+    InvalidGenesisMiner {
+        address: String,
+        error: AddressError,
+    },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::CurrentDirectory { message } => {
+                write!(
+                    f,
+                    "Configuration failed: Unable to read current directory: {}",
+                    message
+                )
+            }
+            ConfigError::Read { path, message } => {
+                write!(
+                    f,
+                    "Configuration failed: Could not read the config file at {}: {}",
+                    path, message
+                )
+            }
+            ConfigError::Parse { path, message } => {
+                write!(
+                    f,
+                    "Configuration failed: Could not parse the config file at {}: {}",
+                    path, message
+                )
+            }
+            ConfigError::TotalSupplyLessThanPreMined {
+                total_supply,
+                pre_mined,
+            } => {
+                write!(
+                    f,
+                    "Configuration failed: Total supply {} is less than the pre-mined {}.",
+                    total_supply, pre_mined
+                )
+            }
+            ConfigError::InvalidGenesisBits { bits } => {
+                write!(
+                    f,
+                    "Configuration failed: Genesis bits {:#010x} do not encode a valid target.",
+                    bits
+                )
+            }
+            ConfigError::InvalidGenesisMiner { address, error } => {
+                write!(
+                    f,
+                    "Configuration failed: Genesis miner {} is not a valid address. {}",
+                    address, error
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::InvalidGenesisMiner { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn config_error_message_formatting() {
+        let error = ConfigError::Read {
+            path: "config.yml".to_string(),
+            message: "not found".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Configuration failed: Could not read the config file at config.yml: not found",
+            "Display output for Read is incorrect"
+        );
+
+        let error = ConfigError::TotalSupplyLessThanPreMined {
+            total_supply: 100,
+            pre_mined: 200,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Configuration failed: Total supply 100 is less than the pre-mined 200.",
+            "Display output for TotalSupplyLessThanPreMined is incorrect"
+        );
+
+        let error = ConfigError::InvalidGenesisBits { bits: 0 };
+        assert_eq!(
+            format!("{}", error),
+            "Configuration failed: Genesis bits 0x00000000 do not encode a valid target.",
+            "Display output for InvalidGenesisBits is incorrect"
+        );
+
+        let error = ConfigError::InvalidGenesisMiner {
+            address: "MINER".to_string(),
+            error: AddressError::InvalidChecksum,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Configuration failed: Genesis miner MINER is not a valid address. Invalid address: Checksum mismatch.",
+            "Display output for InvalidGenesisMiner is incorrect"
+        );
+    }
+}
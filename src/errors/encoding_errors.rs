@@ -0,0 +1,100 @@
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    UnexpectedEnd,
+    UnsupportedVersion { version: u8 },
+    InvalidUtf8,
+    InvalidPresenceTag { tag: u8 },
+    InvalidPublicKey,
+    InvalidSignature,
+    TrailingBytes { count: usize },
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => {
+                write!(f, "Decoding failed: Unexpected end of input.")
+            }
+            DecodeError::UnsupportedVersion { version } => {
+                write!(
+                    f,
+                    "Decoding failed: Unsupported encoding version {}.",
+                    version
+                )
+            }
+            DecodeError::InvalidUtf8 => {
+                write!(f, "Decoding failed: String is not valid UTF-8.")
+            }
+            DecodeError::InvalidPresenceTag { tag } => {
+                write!(f, "Decoding failed: Invalid presence tag {}.", tag)
+            }
+            DecodeError::InvalidPublicKey => {
+                write!(f, "Decoding failed: Invalid public key.")
+            }
+            DecodeError::InvalidSignature => {
+                write!(f, "Decoding failed: Invalid signature.")
+            }
+            DecodeError::TrailingBytes { count } => {
+                write!(f, "Decoding failed: {} trailing bytes.", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn decode_error_message_formatting() {
+        let error = DecodeError::UnexpectedEnd;
+        assert_eq!(
+            format!("{}", error),
+            "Decoding failed: Unexpected end of input.",
+            "Display output for UnexpectedEnd is incorrect"
+        );
+
+        let error = DecodeError::UnsupportedVersion { version: 9 };
+        assert_eq!(
+            format!("{}", error),
+            "Decoding failed: Unsupported encoding version 9.",
+            "Display output for UnsupportedVersion is incorrect"
+        );
+
+        let error = DecodeError::InvalidUtf8;
+        assert_eq!(
+            format!("{}", error),
+            "Decoding failed: String is not valid UTF-8.",
+            "Display output for InvalidUtf8 is incorrect"
+        );
+
+        let error = DecodeError::InvalidPresenceTag { tag: 2 };
+        assert_eq!(
+            format!("{}", error),
+            "Decoding failed: Invalid presence tag 2.",
+            "Display output for InvalidPresenceTag is incorrect"
+        );
+
+        let error = DecodeError::InvalidPublicKey;
+        assert_eq!(
+            format!("{}", error),
+            "Decoding failed: Invalid public key.",
+            "Display output for InvalidPublicKey is incorrect"
+        );
+
+        let error = DecodeError::InvalidSignature;
+        assert_eq!(
+            format!("{}", error),
+            "Decoding failed: Invalid signature.",
+            "Display output for InvalidSignature is incorrect"
+        );
+
+        let error = DecodeError::TrailingBytes { count: 3 };
+        assert_eq!(
+            format!("{}", error),
+            "Decoding failed: 3 trailing bytes.",
+            "Display output for TrailingBytes is incorrect"
+        );
+    }
+}
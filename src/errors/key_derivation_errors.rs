@@ -0,0 +1,122 @@
+#[derive(Debug, PartialEq)]
+pub enum KeyDerivationError {
+    InvalidWordCount { count: usize },
+    UnknownWord { word: String },
+    InvalidChecksum,
+    InvalidMnemonic { message: String },
+    InvalidSeedLength { length: usize },
+    InvalidPath { path: String },
+    InvalidKey { index: u32 },
+}
+
+impl std::fmt::Display for KeyDerivationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyDerivationError::InvalidWordCount { count } => {
+                write!(
+                    f,
+                    "Key derivation failed: A mnemonic has 12, 15, 18, 21 or 24 words, not {}.",
+                    count
+                )
+            }
+            KeyDerivationError::UnknownWord { word } => {
+                write!(
+                    f,
+                    "Key derivation failed: Unknown mnemonic word '{}'.",
+                    word
+                )
+            }
+            KeyDerivationError::InvalidChecksum => {
+                write!(f, "Key derivation failed: Invalid mnemonic checksum.")
+            }
+            KeyDerivationError::InvalidMnemonic { message } => {
+                write!(f, "Key derivation failed: Invalid mnemonic: {}", message)
+            }
+            KeyDerivationError::InvalidSeedLength { length } => {
+                write!(
+                    f,
+                    "Key derivation failed: A seed has 16 to 64 bytes, not {}.",
+                    length
+                )
+            }
+            KeyDerivationError::InvalidPath { path } => {
+                write!(
+                    f,
+                    "Key derivation failed: Invalid derivation path '{}'.",
+                    path
+                )
+            }
+            KeyDerivationError::InvalidKey { index } => {
+                write!(
+                    f,
+                    "Key derivation failed: Child {} does not yield a valid key.",
+                    index
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyDerivationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn key_derivation_error_message_formatting() {
+        let error = KeyDerivationError::InvalidWordCount { count: 11 };
+        assert_eq!(
+            format!("{}", error),
+            "Key derivation failed: A mnemonic has 12, 15, 18, 21 or 24 words, not 11.",
+            "Display output for InvalidWordCount is incorrect"
+        );
+
+        let error = KeyDerivationError::UnknownWord {
+            word: "bitcoin".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Key derivation failed: Unknown mnemonic word 'bitcoin'.",
+            "Display output for UnknownWord is incorrect"
+        );
+
+        let error = KeyDerivationError::InvalidChecksum;
+        assert_eq!(
+            format!("{}", error),
+            "Key derivation failed: Invalid mnemonic checksum.",
+            "Display output for InvalidChecksum is incorrect"
+        );
+
+        let error = KeyDerivationError::InvalidMnemonic {
+            message: "ambiguous language".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Key derivation failed: Invalid mnemonic: ambiguous language",
+            "Display output for InvalidMnemonic is incorrect"
+        );
+
+        let error = KeyDerivationError::InvalidSeedLength { length: 8 };
+        assert_eq!(
+            format!("{}", error),
+            "Key derivation failed: A seed has 16 to 64 bytes, not 8.",
+            "Display output for InvalidSeedLength is incorrect"
+        );
+
+        let error = KeyDerivationError::InvalidPath {
+            path: "m/x".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Key derivation failed: Invalid derivation path 'm/x'.",
+            "Display output for InvalidPath is incorrect"
+        );
+
+        let error = KeyDerivationError::InvalidKey { index: 3 };
+        assert_eq!(
+            format!("{}", error),
+            "Key derivation failed: Child 3 does not yield a valid key.",
+            "Display output for InvalidKey is incorrect"
+        );
+    }
+}
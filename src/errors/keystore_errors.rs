@@ -0,0 +1,100 @@
+#[derive(Debug, PartialEq)]
+pub enum KeystoreError {
+    Io { path: String, message: String },
+    Corrupted { message: String },
+    UnsupportedVersion { version: u32 },
+    InvalidKdfParameters { message: String },
+    WrongPassword,
+}
+
+impl KeystoreError {
+    /// Wraps an I/O error that occurred while accessing the given path.
+    pub fn io(path: &std::path::Path, error: std::io::Error) -> Self {
+        KeystoreError::Io {
+            path: path.display().to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    /// Reports a keystore whose content cannot be trusted.
+    pub fn corrupted(message: impl std::fmt::Display) -> Self {
+        KeystoreError::Corrupted {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeystoreError::Io { path, message } => {
+                write!(f, "Keystore failed: Could not access {}: {}", path, message)
+            }
+            KeystoreError::Corrupted { message } => {
+                write!(f, "Keystore failed: The keystore is corrupted: {}", message)
+            }
+            KeystoreError::UnsupportedVersion { version } => {
+                write!(
+                    f,
+                    "Keystore failed: Unsupported keystore version {}.",
+                    version
+                )
+            }
+            KeystoreError::InvalidKdfParameters { message } => {
+                write!(f, "Keystore failed: Invalid KDF parameters: {}", message)
+            }
+            KeystoreError::WrongPassword => {
+                write!(f, "Keystore failed: Wrong password.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn keystore_error_message_formatting() {
+        let error = KeystoreError::Io {
+            path: "wallet.yml".to_string(),
+            message: "No such file or directory".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Keystore failed: Could not access wallet.yml: No such file or directory",
+            "Display output for Io is incorrect"
+        );
+
+        let error = KeystoreError::corrupted("Invalid nonce length");
+        assert_eq!(
+            format!("{}", error),
+            "Keystore failed: The keystore is corrupted: Invalid nonce length",
+            "Display output for Corrupted is incorrect"
+        );
+
+        let error = KeystoreError::UnsupportedVersion { version: 7 };
+        assert_eq!(
+            format!("{}", error),
+            "Keystore failed: Unsupported keystore version 7.",
+            "Display output for UnsupportedVersion is incorrect"
+        );
+
+        let error = KeystoreError::InvalidKdfParameters {
+            message: "invalid scrypt parameters".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Keystore failed: Invalid KDF parameters: invalid scrypt parameters",
+            "Display output for InvalidKdfParameters is incorrect"
+        );
+
+        let error = KeystoreError::WrongPassword;
+        assert_eq!(
+            format!("{}", error),
+            "Keystore failed: Wrong password.",
+            "Display output for WrongPassword is incorrect"
+        );
+    }
+}
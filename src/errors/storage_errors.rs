@@ -0,0 +1,85 @@
+use crate::errors::encoding_errors::DecodeError;
+
+#[derive(Debug, PartialEq)]
+pub enum StorageError {
+    Io { path: String, message: String },
+    ChecksumMismatch { offset: u64 },
+    InvalidRecord { offset: u64, error: DecodeError },
+}
+
+impl StorageError {
+    /// Wraps an I/O error that occurred while accessing the given path.
+    pub fn io(path: &std::path::Path, error: std::io::Error) -> Self {
+        StorageError::Io {
+            path: path.display().to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::Io { path, message } => {
+                write!(f, "Storage failed: Could not access {}: {}", path, message)
+            }
+            StorageError::ChecksumMismatch { offset } => {
+                write!(
+                    f,
+                    "Storage failed: Checksum mismatch in record at offset {}.",
+                    offset
+                )
+            }
+            StorageError::InvalidRecord { offset, error } => {
+                write!(
+                    f,
+                    "Storage failed: Record at offset {} is invalid. {}",
+                    offset, error
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::InvalidRecord { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn storage_error_message_formatting() {
+        let error = StorageError::Io {
+            path: "data/blocks.dat".to_string(),
+            message: "permission denied".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Storage failed: Could not access data/blocks.dat: permission denied",
+            "Display output for Io is incorrect"
+        );
+
+        let error = StorageError::ChecksumMismatch { offset: 42 };
+        assert_eq!(
+            format!("{}", error),
+            "Storage failed: Checksum mismatch in record at offset 42.",
+            "Display output for ChecksumMismatch is incorrect"
+        );
+
+        let error = StorageError::InvalidRecord {
+            offset: 7,
+            error: DecodeError::UnexpectedEnd,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Storage failed: Record at offset 7 is invalid. Decoding failed: Unexpected end of input.",
+            "Display output for InvalidRecord is incorrect"
+        );
+    }
+}
@@ -1,8 +1,14 @@
+use crate::errors::address_errors::AddressError;
+
 #[derive(Debug, PartialEq)]
 pub enum TransactionError {
     AddressCannotBeEmpty,
     SenderAndReceiverCannotBeTheSame,
     AmountMustBeGreaterThanZero,
+    InvalidAddress {
+        address: String,
+        error: AddressError,
+    },
     InsufficientBalance {
         sender: String,
         requested: u64,
@@ -12,6 +18,30 @@ pub enum TransactionError {
         sender: String,
     },
     BalanceOverflow,
+    MissingPublicKey,
+    MissingSignature,
+    PublicKeyDoesNotMatchSender {
+        sender: String,
+    },
+    InvalidSignature,
+    InvalidNonce {
+        sender: String,
+        expected: u64,
+        found: u64,
+    },
+    NonceTooFarAhead {
+        sender: String,
+        next: u64,
+        found: u64,
+    },
+    Expired {
+        valid_until: u32,
+        height: u32,
+    },
+    AlreadyInMempool,
+    MempoolFull {
+        minimum_fee: u64,
+    },
 }
 
 impl std::fmt::Display for TransactionError {
@@ -26,6 +56,13 @@ impl std::fmt::Display for TransactionError {
             TransactionError::AmountMustBeGreaterThanZero => {
                 write!(f, "Transaction must be greater than zero.")
             }
+            TransactionError::InvalidAddress { address, error } => {
+                write!(
+                    f,
+                    "Transaction rejected: Address {} is invalid. {}",
+                    address, error
+                )
+            }
             TransactionError::InsufficientBalance {
                 sender,
                 requested,
@@ -43,6 +80,73 @@ impl std::fmt::Display for TransactionError {
             TransactionError::BalanceOverflow => {
                 write!(f, "Transaction rejected: Balance overflow.")
             }
+            TransactionError::MissingPublicKey => {
+                write!(f, "Transaction rejected: Public key is missing.")
+            }
+            TransactionError::MissingSignature => {
+                write!(f, "Transaction rejected: Signature is missing.")
+            }
+            TransactionError::PublicKeyDoesNotMatchSender { sender } => {
+                write!(
+                    f,
+                    "Transaction rejected: Public key does not match sender {}.",
+                    sender
+                )
+            }
+            TransactionError::InvalidSignature => {
+                write!(f, "Transaction rejected: Invalid signature.")
+            }
+            TransactionError::InvalidNonce {
+                sender,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "Transaction rejected: Invalid nonce for {} ({} expected, {} found).",
+                    sender, expected, found
+                )
+            }
+            TransactionError::NonceTooFarAhead {
+                sender,
+                next,
+                found,
+            } => {
+                write!(
+                    f,
+                    "Transaction rejected: Nonce {} is too far ahead of the next nonce {} of {}.",
+                    found, next, sender
+                )
+            }
+            TransactionError::Expired {
+                valid_until,
+                height,
+            } => {
+                write!(
+                    f,
+                    "Transaction rejected: Expired at height {} (valid until height {}).",
+                    height, valid_until
+                )
+            }
+            TransactionError::AlreadyInMempool => {
+                write!(f, "Transaction rejected: Already in the mempool.")
+            }
+            TransactionError::MempoolFull { minimum_fee } => {
+                write!(
+                    f,
+                    "Transaction rejected: Mempool is full (fee of at least {} required).",
+                    minimum_fee
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransactionError::InvalidAddress { error, .. } => Some(error),
+            _ => None,
         }
     }
 }
@@ -59,6 +163,44 @@ mod tests {
             "Display output for AddressCannotBeEmpty is incorrect"
         );
 
+        let error = TransactionError::Expired {
+            valid_until: 4,
+            height: 5,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Expired at height 5 (valid until height 4).",
+            "Display output for Expired is incorrect"
+        );
+
+        let error = TransactionError::InvalidAddress {
+            address: "Bob".to_string(),
+            error: AddressError::InvalidChecksum,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Address Bob is invalid. Invalid address: Checksum mismatch.",
+            "Display output for InvalidAddress is incorrect"
+        );
+
+        let error = TransactionError::NonceTooFarAhead {
+            sender: "Alice".to_string(),
+            next: 2,
+            found: 9000,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Nonce 9000 is too far ahead of the next nonce 2 of Alice.",
+            "Display output for NonceTooFarAhead is incorrect"
+        );
+
+        let error = TransactionError::MempoolFull { minimum_fee: 11 };
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Mempool is full (fee of at least 11 required).",
+            "Display output for MempoolFull is incorrect"
+        );
+
         let error = TransactionError::BalanceOverflow;
         assert_eq!(
             format!("{}", error),
@@ -99,5 +241,46 @@ mod tests {
             "Transaction rejected: Sender Alice does not exist.",
             "Display output for SenderDoesNotExist is incorrect"
         );
+
+        let error = TransactionError::MissingPublicKey;
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Public key is missing.",
+            "Display output for MissingPublicKey is incorrect"
+        );
+
+        let error = TransactionError::MissingSignature;
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Signature is missing.",
+            "Display output for MissingSignature is incorrect"
+        );
+
+        let error = TransactionError::PublicKeyDoesNotMatchSender {
+            sender: "Alice".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Public key does not match sender Alice.",
+            "Display output for PublicKeyDoesNotMatchSender is incorrect"
+        );
+
+        let error = TransactionError::InvalidSignature;
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Invalid signature.",
+            "Display output for InvalidSignature is incorrect"
+        );
+
+        let error = TransactionError::InvalidNonce {
+            sender: "Alice".to_string(),
+            expected: 2,
+            found: 5,
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Transaction rejected: Invalid nonce for Alice (2 expected, 5 found).",
+            "Display output for InvalidNonce is incorrect"
+        );
     }
 }
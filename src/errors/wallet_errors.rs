@@ -0,0 +1,73 @@
+#[derive(Debug, PartialEq)]
+pub enum WalletError {
+    EmptyLabel,
+    DuplicateLabel { label: String },
+    DuplicateAddress { address: String },
+    UnknownAccount { label: String },
+}
+
+impl std::fmt::Display for WalletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalletError::EmptyLabel => {
+                write!(f, "Wallet failed: Account label cannot be empty.")
+            }
+            WalletError::DuplicateLabel { label } => {
+                write!(f, "Wallet failed: Account '{}' already exists.", label)
+            }
+            WalletError::DuplicateAddress { address } => {
+                write!(
+                    f,
+                    "Wallet failed: Address {} already belongs to an account.",
+                    address
+                )
+            }
+            WalletError::UnknownAccount { label } => {
+                write!(f, "Wallet failed: Unknown account '{}'.", label)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WalletError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn wallet_error_message_formatting() {
+        let error = WalletError::EmptyLabel;
+        assert_eq!(
+            format!("{}", error),
+            "Wallet failed: Account label cannot be empty.",
+            "Display output for EmptyLabel is incorrect"
+        );
+
+        let error = WalletError::DuplicateLabel {
+            label: "savings".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Wallet failed: Account 'savings' already exists.",
+            "Display output for DuplicateLabel is incorrect"
+        );
+
+        let error = WalletError::DuplicateAddress {
+            address: "tok1abc".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Wallet failed: Address tok1abc already belongs to an account.",
+            "Display output for DuplicateAddress is incorrect"
+        );
+
+        let error = WalletError::UnknownAccount {
+            label: "savings".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Wallet failed: Unknown account 'savings'.",
+            "Display output for UnknownAccount is incorrect"
+        );
+    }
+}
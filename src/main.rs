@@ -1,3 +1,5 @@
+#![allow(dead_code)] // Library-style modules are exercised by tests; main only runs demos
+
 use crate::config::Config;
 use crate::core::blockchain::Blockchain;
 use crate::core::transaction::Transaction;
@@ -9,23 +11,56 @@ mod test_utils;
 pub mod config;
 
 mod common {
+    pub mod address;
     pub mod calculate_hash;
     pub mod compute_address_from_pub_key;
+    pub mod encoding;
+    pub mod merkle;
+    pub mod target;
+    pub mod u256;
 }
 
 mod core {
+    pub mod address_index;
     pub mod block;
+    pub mod block_tree;
+    pub mod block_undo;
     pub mod blockchain;
+    pub mod mempool;
+    pub mod miner;
     pub mod token;
     pub mod transaction;
+    pub mod transaction_index;
 }
 
 mod errors {
+    pub mod address_errors;
+    pub mod block_validation_errors;
+    pub mod blockchain_errors;
+    pub mod config_errors;
+    pub mod encoding_errors;
+    pub mod key_derivation_errors;
+    pub mod keystore_errors;
+    pub mod storage_errors;
     pub mod transaction_errors;
+    pub mod wallet_errors;
+}
+
+mod storage {
+    pub mod block_log;
+    pub mod chain_store;
+    pub mod file_store;
+    pub mod memory_store;
 }
 
 mod wallet {
+    pub mod hd_wallet;
+    pub mod keystore;
+    pub mod mnemonic;
+    pub mod transaction_builder;
+    #[allow(clippy::module_inception)]
     pub mod wallet;
+    pub mod wallet_manager;
 }
 
 fn main() {
@@ -39,10 +74,9 @@ fn test_wallet() {
 
     println!("Address: {:?}", address);
 
-    let mut tx = Transaction::new(address.clone(), "TEST_ADDRESS".to_string(), 100);
+    let mut tx = Transaction::new(address.clone(), "TEST_ADDRESS".to_string(), 100, 0, 0);
 
-    let signature = wallet.sign_transaction(&tx.stringify());
-    tx.sign(signature);
+    wallet.sign(&mut tx);
 
     println!("Signature: {:?}", tx.signature);
     println!("Signature Verified: {:?}", tx.verify(&wallet.public_key)); // Now requires public key for verification
@@ -52,40 +86,41 @@ fn test_blockchain() {
     let config: Config = Config::load().unwrap();
     let mut blockchain: Blockchain = Blockchain::new(config).unwrap();
 
-    blockchain
-        .accounts
-        .insert("Alice".to_string(), 1_000_000_000); // 10 LTK
-    blockchain.accounts.insert("Bob".to_string(), 2_500_000_000); // 25 LTK
+    let alice = Wallet::new();
+    let bob = Wallet::new();
 
     // Initialize balances
-    blockchain.accounts.insert("Alice".to_string(), 100);
-    blockchain.accounts.insert("Bob".to_string(), 50);
-
-    println!("Account Balances: {:#?}", blockchain.accounts);
-
-    blockchain
-        .mempool
-        .push(Transaction::new("Alice".to_string(), "Bob".to_string(), 20));
-
-    blockchain
-        .mempool
-        .push(Transaction::new("Bob".to_string(), "Alice".to_string(), 10));
-
-    blockchain.mempool.push(Transaction::new(
-        "Bob".to_string(),
-        "Charlie".to_string(),
-        30,
-    ));
-
-    blockchain
-        .mempool
-        .push(Transaction::new("Bob".to_string(), "Bob".to_string(), 30));
-
-    blockchain.add_block();
+    blockchain.store.balances.insert(alice.get_address(), 100);
+    blockchain.store.balances.insert(bob.get_address(), 50);
+
+    println!("Account Balances: {:#?}", blockchain.store.balances);
+
+    let transfers = [
+        (&alice, bob.get_address(), 20, 0),
+        (&bob, alice.get_address(), 10, 0),
+        (&bob, "Charlie".to_string(), 30, 1),
+        (&bob, bob.get_address(), 30, 2),
+    ];
+    for (wallet, receiver, amount, nonce) in transfers {
+        let submitted = wallet
+            .transaction(&receiver, amount)
+            .nonce(nonce)
+            .build(&blockchain)
+            .and_then(|tx| blockchain.submit_transaction(tx));
+        if let Err(why) = submitted {
+            eprintln!("{}", why);
+        }
+    }
+
+    let miner = Wallet::new();
+    match blockchain.add_block(&miner.get_address()) {
+        Ok(block) => println!("Added block {}", block.index),
+        Err(why) => eprintln!("{}", why),
+    }
 
     // Print the blockchain and account balances
     println!("Blockchain: {:#?}", blockchain);
 
-    let tx_history = blockchain.get_transaction_history(&"Alice".to_string());
+    let tx_history = blockchain.get_transaction_history(&alice.get_address());
     println!("{:#?}", tx_history);
 }
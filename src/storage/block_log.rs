@@ -0,0 +1,364 @@
+use crate::core::block::Block;
+use crate::core::block_undo::BlockUndo;
+use crate::errors::encoding_errors::DecodeError;
+use crate::errors::storage_errors::StorageError;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// Name of the block log file inside the data directory.
+pub const BLOCK_LOG_FILE: &str = "blocks.dat";
+
+/// Name of the undo log file inside the data directory.
+pub const UNDO_LOG_FILE: &str = "undo.dat";
+
+/// Size of a record header: the payload length and the checksum.
+const RECORD_HEADER_SIZE: usize = 8;
+
+/// Value that can be stored in a `RecordLog`, in its own file of the data directory.
+pub trait LogRecord: Sized {
+    const FILE_NAME: &'static str;
+
+    fn encode_record(&self) -> Vec<u8>;
+
+    fn decode_record(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+impl LogRecord for Block {
+    const FILE_NAME: &'static str = BLOCK_LOG_FILE;
+
+    fn encode_record(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    fn decode_record(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Block::decode(bytes)
+    }
+}
+
+impl LogRecord for BlockUndo {
+    const FILE_NAME: &'static str = UNDO_LOG_FILE;
+
+    fn encode_record(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    fn decode_record(bytes: &[u8]) -> Result<Self, DecodeError> {
+        BlockUndo::decode(bytes)
+    }
+}
+
+/// Append-only log of the blocks of a chain, stored in a data directory.
+pub type BlockLog = RecordLog<Block>;
+
+/// Append-only log of the undo records of the blocks after the genesis block.
+pub type UndoLog = RecordLog<BlockUndo>;
+
+/// Append-only log of records, stored in a data directory.
+/// Every value is written as a record of:
+/// - The length of the encoded value as a big-endian `u32`.
+/// - The first four bytes of the SHA-256 hash of the encoded value as checksum.
+/// - The canonical encoding of the value.
+#[derive(Debug)]
+pub struct RecordLog<T: LogRecord> {
+    path: PathBuf,
+    file: File,
+    record_offsets: Vec<u64>, // Start of every record, in order
+    records: PhantomData<T>,
+}
+
+impl<T: LogRecord> RecordLog<T> {
+    /// Opens the log in the given data directory and returns it with its values.
+    /// - Creates the directory and the log if they do not exist yet.
+    /// - Truncates a torn record at the end of the log, left behind by a crash during an append.
+    /// - Fails if a record before the end of the log is damaged.
+    pub fn open<P: AsRef<Path>>(data_dir: P) -> Result<(Self, Vec<T>), StorageError> {
+        let data_dir = data_dir.as_ref();
+        fs::create_dir_all(data_dir).map_err(|e| StorageError::io(data_dir, e))?;
+
+        let path = data_dir.join(T::FILE_NAME);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .map_err(|e| StorageError::io(&path, e))?;
+        let mut bytes: Vec<u8> = Vec::new();
+        file.read_to_end(&mut bytes)
+            .map_err(|e| StorageError::io(&path, e))?;
+
+        let (values, record_offsets, intact_length) = read_records(&bytes)?;
+        if intact_length < bytes.len() {
+            eprintln!(
+                "Truncating torn record at offset {} of {}.",
+                intact_length,
+                path.display()
+            );
+            file.set_len(intact_length as u64)
+                .and_then(|_| file.sync_all())
+                .map_err(|e| StorageError::io(&path, e))?;
+        }
+
+        // Persist the directory entry of a newly created log
+        File::open(data_dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(|e| StorageError::io(data_dir, e))?;
+
+        Ok((
+            RecordLog {
+                path,
+                file,
+                record_offsets,
+                records: PhantomData,
+            },
+            values,
+        ))
+    }
+
+    /// Returns the number of records in the log.
+    pub fn len(&self) -> usize {
+        self.record_offsets.len()
+    }
+
+    /// Returns `true` if the log holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.record_offsets.is_empty()
+    }
+
+    /// Appends the value to the log and flushes it to disk before returning.
+    /// A failed append is truncated again, so the log never ends in a partial record.
+    pub fn append(&mut self, value: &T) -> Result<(), StorageError> {
+        let payload = value.encode_record();
+        let mut record: Vec<u8> = Vec::with_capacity(RECORD_HEADER_SIZE + payload.len());
+        record.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        record.extend_from_slice(&checksum(&payload));
+        record.extend_from_slice(&payload);
+
+        let length = self
+            .file
+            .metadata()
+            .map_err(|e| StorageError::io(&self.path, e))?
+            .len();
+        if let Err(why) = self
+            .file
+            .write_all(&record)
+            .and_then(|_| self.file.sync_data())
+        {
+            let _ = self.file.set_len(length);
+            return Err(StorageError::io(&self.path, why));
+        }
+        self.record_offsets.push(length);
+        Ok(())
+    }
+
+    /// Removes the last record from the log and flushes the truncation to disk.
+    /// Does nothing if the log is empty.
+    pub fn pop(&mut self) -> Result<(), StorageError> {
+        let Some(offset) = self.record_offsets.last().copied() else {
+            return Ok(());
+        };
+        self.file
+            .set_len(offset)
+            .and_then(|_| self.file.sync_data())
+            .map_err(|e| StorageError::io(&self.path, e))?;
+        self.record_offsets.pop();
+        Ok(())
+    }
+}
+
+/// Reads the values stored in the log and returns them with the offsets of their records and
+/// the length of the intact records.
+/// Reading stops at a record that is cut off or fails its checksum at the end of the log.
+fn read_records<T: LogRecord>(bytes: &[u8]) -> Result<(Vec<T>, Vec<u64>, usize), StorageError> {
+    let mut values: Vec<T> = Vec::new();
+    let mut record_offsets: Vec<u64> = Vec::new();
+    let mut offset: usize = 0;
+
+    while offset < bytes.len() {
+        let Some(header) = bytes.get(offset..offset + RECORD_HEADER_SIZE) else {
+            break; // Torn header
+        };
+        let length = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+        let start = offset + RECORD_HEADER_SIZE;
+        let Some(payload) = bytes.get(start..start + length) else {
+            break; // Torn payload
+        };
+        let end = start + length;
+
+        if header[4..] != checksum(payload) {
+            if end == bytes.len() {
+                break; // Torn write of the last record
+            }
+            return Err(StorageError::ChecksumMismatch {
+                offset: offset as u64,
+            });
+        }
+        let value = T::decode_record(payload).map_err(|error| StorageError::InvalidRecord {
+            offset: offset as u64,
+            error,
+        })?;
+
+        values.push(value);
+        record_offsets.push(offset as u64);
+        offset = end;
+    }
+
+    Ok((values, record_offsets, offset))
+}
+
+/// Returns the checksum of a record payload.
+fn checksum(payload: &[u8]) -> [u8; 4] {
+    Sha256::digest(payload)[..4].try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transaction::Transaction;
+    use crate::test_utils::EASY_BITS;
+    use tempfile::tempdir;
+
+    fn blocks(count: u32) -> Vec<Block> {
+        let mut blocks: Vec<Block> = vec![Block::new(
+            0,
+            vec![],
+            "GENESIS".to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        )];
+        for index in 1..count {
+            let transactions = vec![Transaction::coinbase("Miner".to_string(), 50, index)];
+            let previous_hash = blocks.last().unwrap().hash.clone();
+            blocks.push(Block::new(
+                index,
+                transactions,
+                previous_hash,
+                "Miner".to_string(),
+                EASY_BITS,
+            ));
+        }
+        blocks
+    }
+
+    fn log_path(data_dir: &Path) -> PathBuf {
+        data_dir.join(BLOCK_LOG_FILE)
+    }
+    #[test]
+    fn appended_blocks_are_reloaded() {
+        let data_dir = tempdir().unwrap();
+        let blocks = blocks(3);
+
+        let (mut log, stored) = BlockLog::open(data_dir.path()).unwrap();
+        assert!(stored.is_empty(), "New log should be empty");
+        for block in &blocks {
+            log.append(block).unwrap();
+        }
+        drop(log);
+
+        let (_, stored) = BlockLog::open(data_dir.path()).unwrap();
+        assert_eq!(
+            stored, blocks,
+            "Reloaded blocks should match the appended ones"
+        );
+    }
+    #[test]
+    fn torn_trailing_record_is_truncated() {
+        let data_dir = tempdir().unwrap();
+        let blocks = blocks(3);
+
+        let (mut log, _) = BlockLog::open(data_dir.path()).unwrap();
+        log.append(&blocks[0]).unwrap();
+        log.append(&blocks[1]).unwrap();
+        drop(log);
+        let intact_length = fs::metadata(log_path(data_dir.path())).unwrap().len();
+
+        // Simulate a crash halfway through appending the third block
+        let payload = blocks[2].encode();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(log_path(data_dir.path()))
+            .unwrap();
+        file.write_all(&(payload.len() as u32).to_be_bytes())
+            .unwrap();
+        file.write_all(&checksum(&payload)).unwrap();
+        file.write_all(&payload[..payload.len() / 2]).unwrap();
+        drop(file);
+
+        let (mut log, stored) = BlockLog::open(data_dir.path()).unwrap();
+        assert_eq!(stored, blocks[..2], "Intact blocks should be reloaded");
+        assert_eq!(
+            fs::metadata(log_path(data_dir.path())).unwrap().len(),
+            intact_length,
+            "Torn record should be truncated"
+        );
+
+        log.append(&blocks[2]).unwrap();
+        drop(log);
+        let (_, stored) = BlockLog::open(data_dir.path()).unwrap();
+        assert_eq!(stored, blocks, "Appending should continue after truncation");
+    }
+    #[test]
+    fn popped_blocks_are_removed_from_disk() {
+        let data_dir = tempdir().unwrap();
+        let blocks = blocks(3);
+
+        let (mut log, _) = BlockLog::open(data_dir.path()).unwrap();
+        for block in &blocks {
+            log.append(block).unwrap();
+        }
+        log.pop().unwrap();
+        drop(log);
+
+        let (mut log, stored) = BlockLog::open(data_dir.path()).unwrap();
+        assert_eq!(stored, blocks[..2], "Popped block should not be reloaded");
+
+        // Popping works for records read on open as well
+        log.pop().unwrap();
+        log.pop().unwrap();
+        log.pop().unwrap();
+        drop(log);
+        let (_, stored) = BlockLog::open(data_dir.path()).unwrap();
+        assert!(stored.is_empty(), "All blocks should be popped");
+    }
+    #[test]
+    fn trailing_record_with_bad_checksum_is_truncated() {
+        let data_dir = tempdir().unwrap();
+        let blocks = blocks(2);
+
+        let (mut log, _) = BlockLog::open(data_dir.path()).unwrap();
+        log.append(&blocks[0]).unwrap();
+        log.append(&blocks[1]).unwrap();
+        drop(log);
+
+        // Damage the last byte, as a write that never reached the disk would
+        let mut bytes = fs::read(log_path(data_dir.path())).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(log_path(data_dir.path()), &bytes).unwrap();
+
+        let (_, stored) = BlockLog::open(data_dir.path()).unwrap();
+        assert_eq!(stored, blocks[..1], "Damaged last record should be dropped");
+    }
+    #[test]
+    fn damaged_record_before_the_end_is_rejected() {
+        let data_dir = tempdir().unwrap();
+        let blocks = blocks(2);
+
+        let (mut log, _) = BlockLog::open(data_dir.path()).unwrap();
+        log.append(&blocks[0]).unwrap();
+        log.append(&blocks[1]).unwrap();
+        drop(log);
+
+        let mut bytes = fs::read(log_path(data_dir.path())).unwrap();
+        bytes[RECORD_HEADER_SIZE + 2] ^= 0xff;
+        fs::write(log_path(data_dir.path()), &bytes).unwrap();
+
+        assert_eq!(
+            BlockLog::open(data_dir.path()).err(),
+            Some(StorageError::ChecksumMismatch { offset: 0 }),
+            "Damage before the end of the log is not a torn write"
+        );
+    }
+}
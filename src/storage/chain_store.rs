@@ -0,0 +1,67 @@
+use crate::core::block::Block;
+use crate::core::block_undo::BlockUndo;
+use crate::errors::storage_errors::StorageError;
+
+/// Storage backend of a blockchain.
+/// Holds the blocks of the chain, the undo records of the blocks after the genesis block and
+/// the account state that results from executing them.
+/// - Only blocks and undo records have to be persisted. Account writes are not durable: the
+///   blockchain rebuilds balances and nonces by replaying the stored blocks whenever it is
+///   created on top of a store that already holds a chain.
+/// - The consensus rules in `core::blockchain` only access the chain through this trait.
+pub trait ChainStore {
+    /// Returns the number of stored blocks.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if no blocks are stored.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the block at the given height.
+    fn block_by_height(&self, height: u32) -> Option<&Block>;
+
+    /// Returns the block with the given hash.
+    fn block_by_hash(&self, hash: &str) -> Option<&Block>;
+
+    /// Returns the block at the tip of the chain.
+    fn last_block(&self) -> Option<&Block> {
+        let height = self.len().checked_sub(1)?;
+        self.block_by_height(height as u32)
+    }
+
+    /// Iterates over the stored blocks, starting at the genesis block.
+    fn blocks(&self) -> Box<dyn Iterator<Item = &Block> + '_>;
+
+    /// Appends a block to the tip of the chain.
+    fn append_block(&mut self, block: Block) -> Result<(), StorageError>;
+
+    /// Removes the block at the tip of the chain and its undo record, and returns the block.
+    /// The account state is not reverted; that is up to the caller (see `block_undo`).
+    fn pop_block(&mut self) -> Result<Option<Block>, StorageError>;
+
+    /// Returns the undo record of the block at the given height, if it is stored.
+    fn block_undo(&self, height: u32) -> Option<&BlockUndo>;
+
+    /// Stores the undo record of the first block after the genesis block that has none yet.
+    /// Undo records are appended in block order, after their block.
+    fn append_undo(&mut self, undo: BlockUndo) -> Result<(), StorageError>;
+
+    /// Returns the balance of the account, or `None` if the account does not exist.
+    fn balance(&self, address: &str) -> Option<u64>;
+
+    /// Sets the balance of the account, creating the account if it does not exist.
+    fn set_balance(&mut self, address: &str, balance: u64);
+
+    /// Returns the next expected transaction nonce of the account.
+    fn nonce(&self, address: &str) -> u64;
+
+    /// Sets the next expected transaction nonce of the account.
+    fn set_nonce(&mut self, address: &str, nonce: u64);
+
+    /// Removes the account, as if it never existed.
+    fn remove_account(&mut self, address: &str);
+
+    /// Removes all accounts, so the state can be rebuilt by executing the blocks again.
+    fn clear_accounts(&mut self);
+}
@@ -0,0 +1,206 @@
+use crate::core::block::Block;
+use crate::core::block_undo::BlockUndo;
+use crate::errors::storage_errors::StorageError;
+use crate::storage::block_log::{BlockLog, UndoLog};
+use crate::storage::chain_store::ChainStore;
+use crate::storage::memory_store::MemoryStore;
+use std::path::Path;
+
+/// Chain store that persists its blocks in a `BlockLog` and their undo records in an `UndoLog`
+/// in a data directory.
+/// - Every block and undo record is written to disk before `append_block` or `append_undo`
+///   returns.
+/// - The blocks are also kept in memory, so reads never touch the disk.
+/// - Balances and nonces are never written to disk. Replaying `blocks.dat` is how they
+///   persist: `Blockchain::open` executes every stored block again to rebuild them, so
+///   opening a long chain takes time proportional to its length.
+#[derive(Debug)]
+pub struct FileStore {
+    block_log: BlockLog,
+    undo_log: UndoLog,
+    memory: MemoryStore,
+}
+
+impl FileStore {
+    /// Opens the store in the given data directory and loads its blocks and undo records.
+    /// - Undo records left behind by blocks that were popped before a crash are removed.
+    /// - The account state starts out empty.
+    pub fn open<P: AsRef<Path>>(data_dir: P) -> Result<Self, StorageError> {
+        let (block_log, blocks) = BlockLog::open(&data_dir)?;
+        let (mut undo_log, mut undos) = UndoLog::open(&data_dir)?;
+        while undo_log.len() > blocks.len().saturating_sub(1) {
+            undo_log.pop()?;
+            undos.pop();
+        }
+
+        let memory = MemoryStore {
+            blocks,
+            undos,
+            ..MemoryStore::default()
+        };
+        Ok(FileStore {
+            block_log,
+            undo_log,
+            memory,
+        })
+    }
+}
+
+impl ChainStore for FileStore {
+    fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    fn block_by_height(&self, height: u32) -> Option<&Block> {
+        self.memory.block_by_height(height)
+    }
+
+    fn block_by_hash(&self, hash: &str) -> Option<&Block> {
+        self.memory.block_by_hash(hash)
+    }
+
+    fn blocks(&self) -> Box<dyn Iterator<Item = &Block> + '_> {
+        self.memory.blocks()
+    }
+
+    fn append_block(&mut self, block: Block) -> Result<(), StorageError> {
+        self.block_log.append(&block)?;
+        self.memory.append_block(block)
+    }
+
+    fn pop_block(&mut self) -> Result<Option<Block>, StorageError> {
+        if self.memory.is_empty() {
+            return Ok(None);
+        }
+        self.block_log.pop()?;
+        if self.undo_log.len() >= self.memory.len() - 1 {
+            self.undo_log.pop()?;
+        }
+        self.memory.pop_block()
+    }
+
+    fn block_undo(&self, height: u32) -> Option<&BlockUndo> {
+        self.memory.block_undo(height)
+    }
+
+    fn append_undo(&mut self, undo: BlockUndo) -> Result<(), StorageError> {
+        self.undo_log.append(&undo)?;
+        self.memory.append_undo(undo)
+    }
+
+    fn balance(&self, address: &str) -> Option<u64> {
+        self.memory.balance(address)
+    }
+
+    fn set_balance(&mut self, address: &str, balance: u64) {
+        self.memory.set_balance(address, balance);
+    }
+
+    fn nonce(&self, address: &str) -> u64 {
+        self.memory.nonce(address)
+    }
+
+    fn set_nonce(&mut self, address: &str, nonce: u64) {
+        self.memory.set_nonce(address, nonce);
+    }
+
+    fn remove_account(&mut self, address: &str) {
+        self.memory.remove_account(address);
+    }
+
+    fn clear_accounts(&mut self) {
+        self.memory.clear_accounts();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EASY_BITS;
+    use tempfile::tempdir;
+    #[test]
+    fn appended_and_popped_blocks_survive_reopening() {
+        let data_dir = tempdir().unwrap();
+        let genesis = Block::new(
+            0,
+            vec![],
+            "GENESIS".to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+        let next = Block::new(
+            1,
+            vec![],
+            genesis.hash.clone(),
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+
+        let mut store = FileStore::open(data_dir.path()).unwrap();
+        store.append_block(genesis.clone()).unwrap();
+        store.append_block(next.clone()).unwrap();
+        store.set_balance("Alice", 100);
+        drop(store);
+
+        let mut store = FileStore::open(data_dir.path()).unwrap();
+        assert_eq!(store.len(), 2, "Blocks should be reloaded");
+        assert_eq!(store.block_by_hash(&next.hash), Some(&next));
+        assert_eq!(
+            store.balance("Alice"),
+            None,
+            "Account state is rebuilt by the blockchain, not stored"
+        );
+
+        assert_eq!(store.pop_block(), Ok(Some(next)));
+        drop(store);
+        let store = FileStore::open(data_dir.path()).unwrap();
+        assert_eq!(
+            store.blocks().collect::<Vec<&Block>>(),
+            vec![&genesis],
+            "Popped block should be removed from disk"
+        );
+    }
+    #[test]
+    fn undo_records_survive_reopening_with_their_blocks() {
+        let data_dir = tempdir().unwrap();
+        let genesis = Block::new(
+            0,
+            vec![],
+            "GENESIS".to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+        let next = Block::new(
+            1,
+            vec![],
+            genesis.hash.clone(),
+            "Miner".to_string(),
+            EASY_BITS,
+        );
+        let undo = BlockUndo {
+            circulating_supply: 50,
+            accounts: vec![],
+        };
+
+        let mut store = FileStore::open(data_dir.path()).unwrap();
+        store.append_block(genesis).unwrap();
+        store.append_block(next).unwrap();
+        store.append_undo(undo.clone()).unwrap();
+        drop(store);
+
+        let mut store = FileStore::open(data_dir.path()).unwrap();
+        assert_eq!(
+            store.block_undo(1),
+            Some(&undo),
+            "Undo record should be reloaded"
+        );
+
+        // An undo record without its block, as left behind by a crash, is removed
+        store.block_log.pop().unwrap();
+        drop(store);
+        let store = FileStore::open(data_dir.path()).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.block_undo(1), None);
+        assert!(store.undo_log.is_empty(), "Undo log should be truncated");
+    }
+}
@@ -0,0 +1,177 @@
+use crate::core::block::Block;
+use crate::core::block_undo::BlockUndo;
+use crate::errors::storage_errors::StorageError;
+use crate::storage::chain_store::ChainStore;
+use std::collections::HashMap;
+
+/// Chain store that keeps everything in memory and loses it when dropped.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    pub blocks: Vec<Block>,
+    pub undos: Vec<BlockUndo>, // Undo record of the block at height `i + 1` at index `i`
+    pub balances: HashMap<String, u64>,
+    pub nonces: HashMap<String, u64>, // Next expected transaction nonce per account
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl ChainStore for MemoryStore {
+    fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    fn block_by_height(&self, height: u32) -> Option<&Block> {
+        self.blocks.get(height as usize)
+    }
+
+    fn block_by_hash(&self, hash: &str) -> Option<&Block> {
+        self.blocks.iter().find(|block| block.hash == hash)
+    }
+
+    fn blocks(&self) -> Box<dyn Iterator<Item = &Block> + '_> {
+        Box::new(self.blocks.iter())
+    }
+
+    fn append_block(&mut self, block: Block) -> Result<(), StorageError> {
+        self.blocks.push(block);
+        Ok(())
+    }
+
+    fn pop_block(&mut self) -> Result<Option<Block>, StorageError> {
+        let block = self.blocks.pop();
+        self.undos.truncate(self.blocks.len().saturating_sub(1));
+        Ok(block)
+    }
+
+    fn block_undo(&self, height: u32) -> Option<&BlockUndo> {
+        self.undos.get((height as usize).checked_sub(1)?)
+    }
+
+    fn append_undo(&mut self, undo: BlockUndo) -> Result<(), StorageError> {
+        self.undos.push(undo);
+        Ok(())
+    }
+
+    fn balance(&self, address: &str) -> Option<u64> {
+        self.balances.get(address).copied()
+    }
+
+    fn set_balance(&mut self, address: &str, balance: u64) {
+        self.balances.insert(address.to_string(), balance);
+    }
+
+    fn nonce(&self, address: &str) -> u64 {
+        self.nonces.get(address).copied().unwrap_or(0)
+    }
+
+    fn set_nonce(&mut self, address: &str, nonce: u64) {
+        self.nonces.insert(address.to_string(), nonce);
+    }
+
+    fn remove_account(&mut self, address: &str) {
+        self.balances.remove(address);
+        self.nonces.remove(address);
+    }
+
+    fn clear_accounts(&mut self) {
+        self.balances.clear();
+        self.nonces.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EASY_BITS;
+
+    fn block(index: u32, previous_hash: &str) -> Block {
+        Block::new(
+            index,
+            vec![],
+            previous_hash.to_string(),
+            "Miner".to_string(),
+            EASY_BITS,
+        )
+    }
+    #[test]
+    fn blocks_are_found_by_height_and_hash() {
+        let mut store = MemoryStore::new();
+        assert!(store.is_empty(), "New store should be empty");
+        assert_eq!(store.last_block(), None, "Empty store has no tip");
+
+        let genesis = block(0, "GENESIS");
+        let next = block(1, &genesis.hash);
+        store.append_block(genesis.clone()).unwrap();
+        store.append_block(next.clone()).unwrap();
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.block_by_height(1), Some(&next));
+        assert_eq!(store.block_by_hash(&genesis.hash), Some(&genesis));
+        assert_eq!(store.block_by_hash("unknown"), None);
+        assert_eq!(store.last_block(), Some(&next));
+        assert_eq!(
+            store.blocks().collect::<Vec<&Block>>(),
+            vec![&genesis, &next],
+            "Blocks should be iterated from genesis to tip"
+        );
+
+        assert_eq!(store.pop_block(), Ok(Some(next)));
+        assert_eq!(store.last_block(), Some(&genesis));
+    }
+    #[test]
+    fn undo_records_follow_their_blocks() {
+        let mut store = MemoryStore::new();
+        let genesis = block(0, "GENESIS");
+        let next = block(1, &genesis.hash);
+        let undo = BlockUndo {
+            circulating_supply: 50,
+            accounts: vec![],
+        };
+        store.append_block(genesis).unwrap();
+        store.append_block(next).unwrap();
+        store.append_undo(undo.clone()).unwrap();
+
+        assert_eq!(
+            store.block_undo(0),
+            None,
+            "Genesis block has no undo record"
+        );
+        assert_eq!(store.block_undo(1), Some(&undo));
+
+        store.pop_block().unwrap();
+        assert_eq!(
+            store.block_undo(1),
+            None,
+            "Undo record should be removed with its block"
+        );
+    }
+    #[test]
+    fn account_state_is_read_and_written() {
+        let mut store = MemoryStore::new();
+        assert_eq!(
+            store.balance("Alice"),
+            None,
+            "Unknown account has no balance"
+        );
+        assert_eq!(store.nonce("Alice"), 0, "Unknown account starts at nonce 0");
+
+        store.set_balance("Alice", 100);
+        store.set_nonce("Alice", 3);
+
+        assert_eq!(store.balance("Alice"), Some(100));
+        assert_eq!(store.nonce("Alice"), 3);
+
+        store.remove_account("Alice");
+        assert_eq!(
+            store.balance("Alice"),
+            None,
+            "Removed account has no balance"
+        );
+        assert_eq!(store.nonce("Alice"), 0);
+    }
+}
@@ -1,4 +1,8 @@
+use crate::common::address::{Address, Network};
 use crate::config::{BlockchainConfig, Config, TokenConfig};
+use crate::core::transaction::Transaction;
+use crate::wallet::wallet::Wallet;
+use sha2::{Digest, Sha256};
 
 pub fn mock_config() -> Config {
     const TOKEN_NAME: &str = "test_name";
@@ -6,9 +10,16 @@ pub fn mock_config() -> Config {
     const DECIMALS: u8 = 8;
     const TOTAL_SUPPLY: u64 = 2_100_000_000_000_000;
     const GENESIS_NAME: &str = "genesis_name";
-    const DIFFICULTY: usize = 2;
+    const GENESIS_BITS: u32 = 0x2000_ffff;
     const GENESIS_PRE_MINED: u64 = 2_100_000;
-    const GENESIS_MINER: &str = "Miner";
+    const INITIAL_SUBSIDY: u64 = 5_000_000_000;
+    const HALVING_INTERVAL: u32 = 210_000;
+    const TARGET_BLOCK_INTERVAL: u64 = 600;
+    const RETARGET_INTERVAL: u32 = 2016;
+    const MAX_MEMPOOL_SIZE: usize = 5000;
+    const MEMPOOL_TTL: u64 = 1_209_600;
+    const MAX_BLOCK_SIZE: usize = 1_000_000;
+    const MAX_BLOCK_TRANSACTIONS: usize = 4_000;
 
     Config {
         token: TokenConfig {
@@ -18,10 +29,59 @@ pub fn mock_config() -> Config {
             total_supply: TOTAL_SUPPLY,
         },
         blockchain: BlockchainConfig {
+            network: Network::default(),
             genesis_hash: GENESIS_NAME.to_string(),
-            difficulty: DIFFICULTY,
+            genesis_bits: GENESIS_BITS,
             genesis_pre_mined: GENESIS_PRE_MINED,
-            genesis_miner: GENESIS_MINER.to_string(),
+            genesis_miner: address("genesis_miner"),
+            initial_subsidy: INITIAL_SUBSIDY,
+            halving_interval: HALVING_INTERVAL,
+            target_block_interval: TARGET_BLOCK_INTERVAL,
+            retarget_interval: RETARGET_INTERVAL,
+            max_mempool_size: MAX_MEMPOOL_SIZE,
+            mempool_ttl: MEMPOOL_TTL,
+            max_block_size: MAX_BLOCK_SIZE,
+            max_block_transactions: MAX_BLOCK_TRANSACTIONS,
         },
     }
-}
\ No newline at end of file
+}
+
+/// Creates a transaction from the wallet's address to the receiver, signed by the wallet.
+pub fn signed_transaction(
+    wallet: &Wallet,
+    receiver: &str,
+    amount: u64,
+    fee: u64,
+    nonce: u64,
+) -> Transaction {
+    let mut transaction = Transaction::new(
+        wallet.get_address(),
+        receiver.to_string(),
+        amount,
+        fee,
+        nonce,
+    );
+    wallet.sign(&mut transaction);
+    transaction
+}
+
+/// Returns the beneficiary address used when mining blocks in tests.
+pub fn miner() -> String {
+    address("test_miner")
+}
+
+/// Bits of a target that about every second hash meets, so tests mine blocks instantly.
+pub const EASY_BITS: u32 = 0x207f_ffff;
+
+/// Returns a well-formed address of the default network standing for the given name,
+/// for receivers that need no wallet.
+pub fn address(name: &str) -> String {
+    let digest = Sha256::digest(name.as_bytes());
+    let mut key_hash = [0u8; 20];
+    key_hash.copy_from_slice(&digest[..20]);
+    Address {
+        network: Network::default(),
+        key_hash,
+    }
+    .encode()
+}
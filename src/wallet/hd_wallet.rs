@@ -0,0 +1,292 @@
+use crate::common::address::Network;
+use crate::common::compute_address_from_pub_key::compute_address_from_pub_key;
+use crate::errors::key_derivation_errors::KeyDerivationError;
+use crate::wallet::mnemonic::Mnemonic;
+use crate::wallet::wallet::Wallet;
+use hmac_sha512::HMAC;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+
+/// Child indexes from `HARDENED` on derive hardened keys, written with a trailing `'`.
+pub const HARDENED: u32 = 1 << 31;
+
+/// Path of the external addresses of the first account, following BIP-44.
+pub const DEFAULT_ACCOUNT_PATH: &str = "m/44'/0'/0'/0";
+
+const MASTER_KEY_SALT: &[u8] = b"Bitcoin seed";
+
+/// A BIP-32 extended private key: a secret key and the chain code its children are
+/// derived with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPrivateKey {
+    pub secret_key: SecretKey,
+    pub chain_code: [u8; 32],
+    pub depth: u8,
+    pub child_number: u32, // Index this key was derived with (0 for the master key)
+}
+
+impl ExtendedPrivateKey {
+    /// Derives the master key of a seed of 16 to 64 bytes.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, KeyDerivationError> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(KeyDerivationError::InvalidSeedLength { length: seed.len() });
+        }
+        let (key, chain_code) = split(HMAC::mac(seed, MASTER_KEY_SALT));
+        Ok(ExtendedPrivateKey {
+            secret_key: SecretKey::from_byte_array(&key)
+                .map_err(|_| KeyDerivationError::InvalidKey { index: 0 })?,
+            chain_code,
+            depth: 0,
+            child_number: 0,
+        })
+    }
+
+    /// Returns the public key of the secret key.
+    pub fn public_key(&self) -> PublicKey {
+        self.secret_key.public_key(&Secp256k1::new())
+    }
+
+    /// Derives the child key at `index`; indexes from `HARDENED` on derive hardened keys,
+    /// which cannot be linked to the parent public key.
+    pub fn derive_child(&self, index: u32) -> Result<Self, KeyDerivationError> {
+        let mut data = Vec::with_capacity(37);
+        if index >= HARDENED {
+            data.push(0);
+            data.extend_from_slice(&self.secret_key.secret_bytes());
+        } else {
+            data.extend_from_slice(&self.public_key().serialize());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let (tweak, chain_code) = split(HMAC::mac(data, self.chain_code));
+        let secret_key = Scalar::from_be_bytes(tweak)
+            .ok()
+            .and_then(|tweak| self.secret_key.add_tweak(&tweak).ok())
+            .ok_or(KeyDerivationError::InvalidKey { index })?;
+        Ok(ExtendedPrivateKey {
+            secret_key,
+            chain_code,
+            depth: self.depth.saturating_add(1),
+            child_number: index,
+        })
+    }
+
+    /// Derives the key at a path such as `m/44'/0'/0'/0/5`, relative to this key.
+    pub fn derive_path(&self, path: &str) -> Result<Self, KeyDerivationError> {
+        parse_path(path)?
+            .into_iter()
+            .try_fold(self.clone(), |key, index| key.derive_child(index))
+    }
+}
+
+/// Parses a derivation path into its child indexes.
+/// - The path starts with `m` and lists indexes below `HARDENED` separated by `/`.
+/// - Indexes followed by `'` or `h` are hardened.
+pub fn parse_path(path: &str) -> Result<Vec<u32>, KeyDerivationError> {
+    let invalid = || KeyDerivationError::InvalidPath {
+        path: path.to_string(),
+    };
+    let mut segments = path.split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid());
+    }
+    segments
+        .map(|segment| {
+            let (number, offset) = match segment.strip_suffix(['\'', 'h']) {
+                Some(number) => (number, HARDENED),
+                None => (segment, 0),
+            };
+            match number.parse::<u32>() {
+                Ok(index) if index < HARDENED && !number.starts_with('+') => Ok(index + offset),
+                _ => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
+/// A hierarchical deterministic wallet: every key derives from one seed, so the mnemonic
+/// backs up all of its addresses.
+#[derive(Debug, Clone)]
+pub struct HdWallet {
+    master: ExtendedPrivateKey,
+    account: ExtendedPrivateKey, // Key at `DEFAULT_ACCOUNT_PATH`
+    network: Network,            // Network of the derived addresses
+}
+
+impl HdWallet {
+    /// Creates a wallet from a mnemonic and an optional passphrase ("" for none).
+    /// Each passphrase opens a different wallet.
+    pub fn from_mnemonic(
+        mnemonic: &Mnemonic,
+        passphrase: &str,
+    ) -> Result<Self, KeyDerivationError> {
+        Self::from_seed(&mnemonic.to_seed(passphrase))
+    }
+
+    /// Creates a wallet from a seed of 16 to 64 bytes.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, KeyDerivationError> {
+        let master = ExtendedPrivateKey::from_seed(seed)?;
+        let account = master.derive_path(DEFAULT_ACCOUNT_PATH)?;
+        Ok(HdWallet {
+            master,
+            account,
+            network: Network::default(),
+        })
+    }
+
+    /// Moves the wallet to another network, changing its addresses but not its keys.
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Returns the master key.
+    pub fn master_key(&self) -> &ExtendedPrivateKey {
+        &self.master
+    }
+
+    /// Returns the wallet of the address at `index` of the default account.
+    pub fn derive_wallet(&self, index: u32) -> Result<Wallet, KeyDerivationError> {
+        if index >= HARDENED {
+            return Err(KeyDerivationError::InvalidPath {
+                path: format!("{}/{}", DEFAULT_ACCOUNT_PATH, index),
+            });
+        }
+        let child = self.account.derive_child(index)?;
+        Ok(Wallet::from_secret_key(child.secret_key).with_network(self.network))
+    }
+
+    /// Returns the wallet at an arbitrary path from the master key.
+    pub fn derive_path(&self, path: &str) -> Result<Wallet, KeyDerivationError> {
+        let key = self.master.derive_path(path)?;
+        Ok(Wallet::from_secret_key(key.secret_key).with_network(self.network))
+    }
+
+    /// Returns the addresses at indexes `0..count` of the default account.
+    pub fn addresses(&self, count: u32) -> Result<Vec<String>, KeyDerivationError> {
+        (0..count)
+            .map(|index| {
+                let child = self.account.derive_child(index)?;
+                Ok(compute_address_from_pub_key(
+                    &child.public_key(),
+                    self.network,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Splits an HMAC output into the key half and the chain code half.
+fn split(output: [u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let mut key = [0u8; 32];
+    let mut chain_code = [0u8; 32];
+    key.copy_from_slice(&output[..32]);
+    chain_code.copy_from_slice(&output[32..]);
+    (key, chain_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret_hex(key: &ExtendedPrivateKey) -> String {
+        hex::encode(key.secret_key.secret_bytes())
+    }
+    #[test]
+    fn derivation_matches_the_bip32_test_vector() {
+        // Arrange: Test vector 1 of BIP-32
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+
+        // Act
+        let master = ExtendedPrivateKey::from_seed(&seed).unwrap();
+        let child = master.derive_path("m/0'/1/2'/2/1000000000").unwrap();
+
+        // Assert
+        assert_eq!(
+            hex::encode(master.chain_code),
+            "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508"
+        );
+        assert_eq!(
+            secret_hex(&master.derive_child(HARDENED).unwrap()),
+            "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea"
+        );
+        assert_eq!(
+            secret_hex(&master.derive_path("m/0h/1").unwrap()),
+            "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368",
+            "Normal children should derive from the parent public key"
+        );
+        assert_eq!(
+            secret_hex(&child),
+            "471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8"
+        );
+        assert_eq!(child.depth, 5);
+        assert_eq!(child.child_number, 1_000_000_000);
+    }
+    #[test]
+    fn paths_are_parsed_strictly() {
+        assert_eq!(
+            parse_path("m/44'/0h/7"),
+            Ok(vec![HARDENED + 44, HARDENED, 7])
+        );
+        assert_eq!(parse_path("m"), Ok(vec![]));
+        for path in [
+            "",
+            "44/0",
+            "m/",
+            "m/x",
+            "m/-1",
+            "m/+1",
+            "m/2147483648",
+            "m/1''",
+        ] {
+            assert_eq!(
+                parse_path(path),
+                Err(KeyDerivationError::InvalidPath {
+                    path: path.to_string()
+                }),
+                "Path '{}' should be rejected",
+                path
+            );
+        }
+    }
+    #[test]
+    fn wallets_recovered_from_the_mnemonic_have_the_same_addresses() {
+        // Arrange
+        let mnemonic = Mnemonic::generate(12).unwrap();
+        let hd_wallet = HdWallet::from_mnemonic(&mnemonic, "").unwrap();
+
+        // Act
+        let recovered =
+            HdWallet::from_mnemonic(&Mnemonic::parse(&mnemonic.phrase()).unwrap(), "").unwrap();
+
+        // Assert
+        let addresses = hd_wallet.addresses(5).unwrap();
+        assert_eq!(recovered.addresses(5).unwrap(), addresses);
+        assert_eq!(
+            addresses
+                .iter()
+                .collect::<std::collections::HashSet<_>>()
+                .len(),
+            5,
+            "Each index should have its own address"
+        );
+        assert_eq!(
+            hd_wallet.derive_wallet(3).unwrap().get_address(),
+            addresses[3]
+        );
+        assert_eq!(
+            hd_wallet
+                .derive_path("m/44'/0'/0'/0/3")
+                .unwrap()
+                .get_address(),
+            addresses[3]
+        );
+        assert_ne!(
+            HdWallet::from_mnemonic(&mnemonic, "passphrase")
+                .unwrap()
+                .addresses(1)
+                .unwrap(),
+            addresses[..1],
+            "A passphrase should open a different wallet"
+        );
+    }
+}
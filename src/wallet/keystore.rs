@@ -0,0 +1,485 @@
+use crate::common::address::{Address, Network};
+use crate::common::compute_address_from_pub_key::compute_address_from_pub_key;
+use crate::errors::keystore_errors::KeystoreError;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use secp256k1::rand::RngCore;
+use secp256k1::{Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Version of the keystore layout written by `Keystore::encrypt`.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+const KDF_NAME: &str = "scrypt";
+const CIPHER_NAME: &str = "chacha20-poly1305";
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const DERIVED_KEY_LENGTH: usize = 64; // Encryption key followed by the password check key
+
+/// Bound on 128·r·2^log_n·p, the bytes scrypt mixes, so a keystore file cannot make us
+/// allocate memory or spend time without limit.
+const MAX_KDF_COST: u64 = 1 << 30;
+
+/// Cost parameters of the scrypt key derivation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub log_n: u8, // Base 2 logarithm of the CPU/memory cost
+    pub r: u32,    // Block size
+    pub p: u32,    // Parallelization
+}
+
+impl Default for KdfParams {
+    /// The parameters recommended for interactive logins (about 32 MiB of memory).
+    fn default() -> Self {
+        KdfParams {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Validates the parameters, rejecting costs (128·r·2^log_n·p bytes) above
+    /// `MAX_KDF_COST`.
+    fn to_scrypt(self) -> Result<scrypt::Params, String> {
+        let cost = 1u64
+            .checked_shl(self.log_n as u32)
+            .and_then(|n| n.checked_mul(128))
+            .and_then(|cost| cost.checked_mul(self.r as u64))
+            .and_then(|cost| cost.checked_mul(self.p as u64));
+        if cost.is_none_or(|cost| cost > MAX_KDF_COST) {
+            return Err(format!(
+                "log_n {}, r {} and p {} exceed the cost of {} bytes",
+                self.log_n, self.r, self.p, MAX_KDF_COST
+            ));
+        }
+        scrypt::Params::new(self.log_n, self.r, self.p, DERIVED_KEY_LENGTH)
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// Key derivation section of a keystore file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfSection {
+    pub name: String,
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String, // Hex encoded
+}
+
+/// Encryption section of a keystore file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CipherSection {
+    pub name: String,
+    pub nonce: String,      // Hex encoded
+    pub ciphertext: String, // Hex encoded secret key followed by the authentication tag
+}
+
+/// A secret key encrypted with a password, as stored in a YAML keystore file.
+/// - The password is stretched with scrypt and a random salt into an encryption key and a
+///   check key.
+/// - The secret key is encrypted with ChaCha20-Poly1305, authenticating the address too.
+/// - `check` holds the SHA-256 hash of the check key, which tells a wrong password apart
+///   from a corrupted file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub address: String,
+    pub kdf: KdfSection,
+    pub cipher: CipherSection,
+    pub check: String, // Hex encoded
+}
+
+/// The only field read before the layout of a keystore file is known.
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+impl Keystore {
+    /// Encrypts the secret key with the password, recording its address on the network.
+    pub fn encrypt(
+        secret_key: &SecretKey,
+        network: Network,
+        password: &str,
+        params: KdfParams,
+    ) -> Result<Self, KeystoreError> {
+        let mut rng = secp256k1::rand::thread_rng();
+        let mut salt = [0u8; SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        let scrypt_params = params
+            .to_scrypt()
+            .map_err(|message| KeystoreError::InvalidKdfParameters { message })?;
+        let derived = derive_key(password, &salt, &scrypt_params);
+        let public_key = secret_key.public_key(&Secp256k1::new());
+        let address = compute_address_from_pub_key(&public_key, network);
+        let ciphertext = cipher(&derived)
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &secret_key.secret_bytes(),
+                    aad: address.as_bytes(),
+                },
+            )
+            .expect("A secret key is short enough to be encrypted");
+
+        Ok(Keystore {
+            version: KEYSTORE_VERSION,
+            address,
+            kdf: KdfSection {
+                name: KDF_NAME.to_string(),
+                log_n: params.log_n,
+                r: params.r,
+                p: params.p,
+                salt: hex::encode(salt),
+            },
+            cipher: CipherSection {
+                name: CIPHER_NAME.to_string(),
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+            },
+            check: password_check(&derived),
+        })
+    }
+
+    /// Decrypts the secret key with the password.
+    /// - Returns `WrongPassword` if the password does not match the check.
+    /// - Returns `Corrupted` if the password matches but the content fails authentication
+    ///   or does not hold the key of the stored address.
+    pub fn decrypt(&self, password: &str) -> Result<SecretKey, KeystoreError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion {
+                version: self.version,
+            });
+        }
+        if self.kdf.name != KDF_NAME {
+            return Err(KeystoreError::corrupted(format!(
+                "Unknown KDF {}",
+                self.kdf.name
+            )));
+        }
+        if self.cipher.name != CIPHER_NAME {
+            return Err(KeystoreError::corrupted(format!(
+                "Unknown cipher {}",
+                self.cipher.name
+            )));
+        }
+        let salt = decode_hex("salt", &self.kdf.salt)?;
+        let nonce = decode_hex("nonce", &self.cipher.nonce)?;
+        let ciphertext = decode_hex("ciphertext", &self.cipher.ciphertext)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(KeystoreError::corrupted("Invalid nonce length"));
+        }
+        let scrypt_params = KdfParams {
+            log_n: self.kdf.log_n,
+            r: self.kdf.r,
+            p: self.kdf.p,
+        }
+        .to_scrypt()
+        .map_err(KeystoreError::corrupted)?;
+
+        let derived = derive_key(password, &salt, &scrypt_params);
+        if password_check(&derived) != self.check {
+            return Err(KeystoreError::WrongPassword);
+        }
+        let secret_bytes = cipher(&derived)
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: self.address.as_bytes(),
+                },
+            )
+            .map_err(|_| KeystoreError::corrupted("Authentication of the encrypted key failed"))?;
+        let secret_key = SecretKey::from_slice(&secret_bytes).map_err(KeystoreError::corrupted)?;
+        let public_key = secret_key.public_key(&Secp256k1::new());
+        if compute_address_from_pub_key(&public_key, self.network()?) != self.address {
+            return Err(KeystoreError::corrupted(
+                "The key does not match the stored address",
+            ));
+        }
+        Ok(secret_key)
+    }
+
+    /// Returns the network of the stored address.
+    pub fn network(&self) -> Result<Network, KeystoreError> {
+        Address::parse(&self.address)
+            .map(|address| address.network)
+            .map_err(KeystoreError::corrupted)
+    }
+
+    /// Writes the keystore to a YAML file, readable only by its owner on Unix.
+    /// The keystore is written to a temporary file next to the target first and renamed
+    /// over it, so a failed write never destroys an existing keystore.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), KeystoreError> {
+        let path = path.as_ref();
+        let content = serde_yaml::to_string(self).map_err(KeystoreError::corrupted)?;
+        let file_name = path.file_name().ok_or_else(|| {
+            KeystoreError::io(
+                path,
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "Not a file path"),
+            )
+        })?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(".tmp");
+        let temp_path = dir.join(temp_name);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let written = options.open(&temp_path).and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(error) = written.and_then(|_| fs::rename(&temp_path, path)) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(KeystoreError::io(path, error));
+        }
+
+        // Persist the directory entry of the renamed file
+        fs::File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(|error| KeystoreError::io(path, error))
+    }
+
+    /// Reads a keystore from a YAML file.
+    /// The version is checked before the rest of the layout.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, KeystoreError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|error| KeystoreError::io(path, error))?;
+        let probe: VersionProbe =
+            serde_yaml::from_str(&content).map_err(KeystoreError::corrupted)?;
+        if probe.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion {
+                version: probe.version,
+            });
+        }
+        serde_yaml::from_str(&content).map_err(KeystoreError::corrupted)
+    }
+}
+
+/// Stretches the password into the encryption key and the check key.
+fn derive_key(password: &str, salt: &[u8], params: &scrypt::Params) -> [u8; DERIVED_KEY_LENGTH] {
+    let mut derived = [0u8; DERIVED_KEY_LENGTH];
+    scrypt::scrypt(password.as_bytes(), salt, params, &mut derived)
+        .expect("The derived key length is valid for scrypt");
+    derived
+}
+
+fn cipher(derived: &[u8; DERIVED_KEY_LENGTH]) -> ChaCha20Poly1305 {
+    ChaCha20Poly1305::new(Key::from_slice(&derived[..32]))
+}
+
+fn password_check(derived: &[u8; DERIVED_KEY_LENGTH]) -> String {
+    hex::encode(Sha256::digest(&derived[32..]))
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, KeystoreError> {
+    hex::decode(value).map_err(|error| KeystoreError::corrupted(format!("{}: {}", field, error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::wallet::Wallet;
+    use tempfile::tempdir;
+
+    // Cheap parameters keeping the tests fast
+    const TEST_PARAMS: KdfParams = KdfParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    fn secret_key() -> SecretKey {
+        SecretKey::from_slice(&[7u8; 32]).unwrap()
+    }
+    #[test]
+    fn keystore_round_trips_through_a_file() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallet.yml");
+        let keystore = Keystore::encrypt(
+            &secret_key(),
+            Network::Mainnet,
+            "correct horse",
+            TEST_PARAMS,
+        )
+        .unwrap();
+
+        // Act
+        keystore.save(&path).unwrap();
+        let loaded = Keystore::load(&path).unwrap();
+
+        // Assert
+        assert_eq!(loaded, keystore);
+        assert_eq!(loaded.decrypt("correct horse"), Ok(secret_key()));
+        assert!(
+            !fs::read_to_string(&path)
+                .unwrap()
+                .contains(&hex::encode(secret_key().secret_bytes())),
+            "The secret key should not be stored in clear"
+        );
+    }
+    #[test]
+    fn saving_replaces_the_keystore_atomically() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallet.yml");
+        let first =
+            Keystore::encrypt(&secret_key(), Network::Mainnet, "first", TEST_PARAMS).unwrap();
+        let second =
+            Keystore::encrypt(&secret_key(), Network::Mainnet, "second", TEST_PARAMS).unwrap();
+        first.save(&path).unwrap();
+
+        // Act
+        second.save(&path).unwrap();
+
+        // Assert
+        assert_eq!(Keystore::load(&path).as_ref(), Ok(&second));
+        assert_eq!(
+            fs::read_dir(dir.path()).unwrap().count(),
+            1,
+            "No temporary file should be left behind"
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            std::os::unix::fs::PermissionsExt::mode(&fs::metadata(&path).unwrap().permissions())
+                & 0o777,
+            0o600
+        );
+
+        // A failed save keeps the existing keystore
+        fs::create_dir(dir.path().join(".wallet.yml.tmp")).unwrap();
+        assert!(matches!(first.save(&path), Err(KeystoreError::Io { .. })));
+        assert_eq!(Keystore::load(&path), Ok(second));
+    }
+    #[test]
+    fn encryptions_use_fresh_salts_and_nonces() {
+        let first =
+            Keystore::encrypt(&secret_key(), Network::Mainnet, "password", TEST_PARAMS).unwrap();
+        let second =
+            Keystore::encrypt(&secret_key(), Network::Mainnet, "password", TEST_PARAMS).unwrap();
+
+        assert_ne!(first.kdf.salt, second.kdf.salt);
+        assert_ne!(first.cipher.nonce, second.cipher.nonce);
+        assert_ne!(first.cipher.ciphertext, second.cipher.ciphertext);
+    }
+    #[test]
+    fn wrong_password_and_corruption_are_told_apart() {
+        let keystore =
+            Keystore::encrypt(&secret_key(), Network::Mainnet, "password", TEST_PARAMS).unwrap();
+
+        assert_eq!(
+            keystore.decrypt("passw0rd"),
+            Err(KeystoreError::WrongPassword)
+        );
+
+        let mut tampered = keystore.clone();
+        let mut ciphertext = hex::decode(&tampered.cipher.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        tampered.cipher.ciphertext = hex::encode(ciphertext);
+        assert!(
+            matches!(
+                tampered.decrypt("password"),
+                Err(KeystoreError::Corrupted { .. })
+            ),
+            "A modified ciphertext should fail authentication"
+        );
+
+        let mut tampered = keystore.clone();
+        tampered.address =
+            compute_address_from_pub_key(&Wallet::new().public_key, Network::Mainnet);
+        assert!(
+            matches!(
+                tampered.decrypt("password"),
+                Err(KeystoreError::Corrupted { .. })
+            ),
+            "The address should be authenticated"
+        );
+
+        let mut tampered = keystore;
+        tampered.cipher.nonce = "zz".to_string();
+        assert!(matches!(
+            tampered.decrypt("password"),
+            Err(KeystoreError::Corrupted { .. })
+        ));
+    }
+    #[test]
+    fn unreadable_files_are_reported() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallet.yml");
+
+        assert!(matches!(
+            Keystore::load(&path),
+            Err(KeystoreError::Io { .. })
+        ));
+
+        fs::write(&path, "version: 1\naddress: [").unwrap();
+        assert!(matches!(
+            Keystore::load(&path),
+            Err(KeystoreError::Corrupted { .. })
+        ));
+
+        fs::write(&path, "version: 2\nlayout: unknown\n").unwrap();
+        assert_eq!(
+            Keystore::load(&path),
+            Err(KeystoreError::UnsupportedVersion { version: 2 }),
+            "Newer layouts should be reported by version"
+        );
+    }
+    #[test]
+    fn invalid_kdf_parameters_are_rejected() {
+        let params = KdfParams {
+            r: 0,
+            ..TEST_PARAMS
+        };
+        assert!(matches!(
+            Keystore::encrypt(&secret_key(), Network::Mainnet, "password", params),
+            Err(KeystoreError::InvalidKdfParameters { .. })
+        ));
+
+        let mut keystore =
+            Keystore::encrypt(&secret_key(), Network::Mainnet, "password", TEST_PARAMS).unwrap();
+        for (log_n, r, p) in [(40, 8, 1), (20, 9, 1), (20, 8, 2), (4, u32::MAX, u32::MAX)] {
+            keystore.kdf.log_n = log_n;
+            keystore.kdf.r = r;
+            keystore.kdf.p = p;
+            assert!(
+                matches!(
+                    keystore.decrypt("password"),
+                    Err(KeystoreError::Corrupted { .. })
+                ),
+                "Costs beyond the limit should not be attempted (log_n {}, r {}, p {})",
+                log_n,
+                r,
+                p
+            );
+        }
+        assert!(
+            KdfParams {
+                log_n: 20,
+                r: 8,
+                p: 1
+            }
+            .to_scrypt()
+            .is_ok(),
+            "Costs at the limit should be allowed"
+        );
+    }
+}
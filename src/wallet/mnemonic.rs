@@ -0,0 +1,128 @@
+use crate::errors::key_derivation_errors::KeyDerivationError;
+use secp256k1::rand::RngCore;
+
+/// Word counts allowed by BIP-39, from 128 to 256 bits of entropy.
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// A BIP-39 mnemonic of the English word list, backing up a wallet seed.
+/// The last word carries a checksum of the entropy, so most typing mistakes are detected
+/// when the mnemonic is parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mnemonic {
+    inner: bip39::Mnemonic,
+}
+
+impl Mnemonic {
+    /// Generates a mnemonic of `word_count` words from fresh random entropy.
+    pub fn generate(word_count: usize) -> Result<Self, KeyDerivationError> {
+        if !WORD_COUNTS.contains(&word_count) {
+            return Err(KeyDerivationError::InvalidWordCount { count: word_count });
+        }
+        // Each word encodes 11 bits, 1 in 33 of which is checksum
+        let mut entropy = vec![0u8; word_count * 11 * 32 / 33 / 8];
+        secp256k1::rand::thread_rng().fill_bytes(&mut entropy);
+        Self::from_entropy(&entropy)
+    }
+
+    /// Encodes the given entropy (16 to 32 bytes, a multiple of 4) as a mnemonic.
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, KeyDerivationError> {
+        bip39::Mnemonic::from_entropy(entropy)
+            .map(|inner| Mnemonic { inner })
+            .map_err(|error| map_error(error, ""))
+    }
+
+    /// Recovers a mnemonic from its words, checking the word count, the words and the
+    /// checksum.
+    pub fn parse(phrase: &str) -> Result<Self, KeyDerivationError> {
+        bip39::Mnemonic::parse_in(bip39::Language::English, phrase)
+            .map(|inner| Mnemonic { inner })
+            .map_err(|error| map_error(error, phrase))
+    }
+
+    /// Returns the words separated by single spaces.
+    pub fn phrase(&self) -> String {
+        self.inner.to_string()
+    }
+
+    /// Returns the number of words.
+    pub fn word_count(&self) -> usize {
+        self.inner.word_count()
+    }
+
+    /// Derives the 64 byte seed of the mnemonic, protected by an optional passphrase.
+    pub fn to_seed(&self, passphrase: &str) -> [u8; 64] {
+        self.inner.to_seed(passphrase)
+    }
+}
+
+/// Maps a `bip39` error, looking up unknown words in the parsed phrase.
+fn map_error(error: bip39::Error, phrase: &str) -> KeyDerivationError {
+    match error {
+        bip39::Error::BadWordCount(count) => KeyDerivationError::InvalidWordCount { count },
+        bip39::Error::UnknownWord(index) => KeyDerivationError::UnknownWord {
+            word: phrase
+                .split_whitespace()
+                .nth(index)
+                .unwrap_or_default()
+                .to_string(),
+        },
+        bip39::Error::InvalidChecksum => KeyDerivationError::InvalidChecksum,
+        error => KeyDerivationError::InvalidMnemonic {
+            message: error.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZERO_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    #[test]
+    fn mnemonic_matches_the_bip39_test_vector() {
+        // Arrange
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+
+        // Act
+        let seed = mnemonic.to_seed("TREZOR");
+
+        // Assert
+        assert_eq!(mnemonic.phrase(), ZERO_PHRASE);
+        assert_eq!(
+            hex::encode(seed),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553\
+             1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+    #[test]
+    fn generated_mnemonics_round_trip() {
+        for word_count in WORD_COUNTS {
+            let mnemonic = Mnemonic::generate(word_count).unwrap();
+
+            assert_eq!(mnemonic.word_count(), word_count);
+            assert_eq!(Mnemonic::parse(&mnemonic.phrase()), Ok(mnemonic));
+        }
+        assert_eq!(
+            Mnemonic::generate(13),
+            Err(KeyDerivationError::InvalidWordCount { count: 13 })
+        );
+    }
+    #[test]
+    fn invalid_phrases_are_rejected() {
+        assert_eq!(
+            Mnemonic::parse(&ZERO_PHRASE.replace("about", "abandon")),
+            Err(KeyDerivationError::InvalidChecksum),
+            "A wrong last word should fail the checksum"
+        );
+        assert_eq!(
+            Mnemonic::parse(&ZERO_PHRASE.replace("about", "bitcoin")),
+            Err(KeyDerivationError::UnknownWord {
+                word: "bitcoin".to_string()
+            })
+        );
+        assert_eq!(
+            Mnemonic::parse("abandon about"),
+            Err(KeyDerivationError::InvalidWordCount { count: 2 })
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use crate::core::blockchain::Blockchain;
+use crate::core::transaction::Transaction;
+use crate::errors::transaction_errors::TransactionError;
+use crate::storage::chain_store::ChainStore;
+use crate::wallet::wallet::Wallet;
+
+/// Builds a signed transaction from a wallet, see `Wallet::transaction`.
+/// - The sender is the wallet's address.
+/// - The nonce defaults to the sender's next nonce, after its pending transactions
+///   (see `Blockchain::next_nonce`).
+/// - The fee defaults to the lowest fee the mempool admits (see `Blockchain::minimum_fee`).
+#[derive(Clone)]
+pub struct TransactionBuilder<'a> {
+    wallet: &'a Wallet,
+    receiver: String,
+    amount: u64,
+    fee: Option<u64>,
+    nonce: Option<u64>,
+    valid_until: Option<u32>,
+}
+
+impl<'a> TransactionBuilder<'a> {
+    /// Starts a transaction of `amount` from the wallet to the receiver.
+    pub fn new(wallet: &'a Wallet, receiver: &str, amount: u64) -> Self {
+        TransactionBuilder {
+            wallet,
+            receiver: receiver.to_string(),
+            amount,
+            fee: None,
+            nonce: None,
+            valid_until: None,
+        }
+    }
+
+    /// Pays the given fee instead of the mempool's minimum fee.
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    /// Uses the given nonce instead of the sender's next nonce; nonces ahead of it are held
+    /// in the mempool until the transactions before them are mined.
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Makes the transaction expire after the block at the given height.
+    pub fn valid_until(mut self, height: u32) -> Self {
+        self.valid_until = Some(height);
+        self
+    }
+
+    /// Fills in the missing fields from the chain state, signs the canonical payload and
+    /// checks that the signed transaction can be submitted (see
+    /// `Blockchain::check_transaction`).
+    pub fn build<S: ChainStore>(
+        self,
+        blockchain: &Blockchain<S>,
+    ) -> Result<Transaction, TransactionError> {
+        let sender = self.wallet.get_address();
+        let nonce = self.nonce.unwrap_or_else(|| blockchain.next_nonce(&sender));
+        let fee = self.fee.unwrap_or_else(|| blockchain.minimum_fee());
+        let mut transaction = Transaction::new(sender, self.receiver, self.amount, fee, nonce);
+        if let Some(height) = self.valid_until {
+            transaction = transaction.with_valid_until(height);
+        }
+
+        self.wallet.sign(&mut transaction);
+        blockchain.check_transaction(&transaction)?;
+        Ok(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{address, miner, mock_config};
+    #[test]
+    fn built_transactions_are_signed_and_take_the_next_nonce() {
+        // Arrange
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+
+        // Act
+        let first = alice
+            .transaction(&address("Bob"), 10)
+            .fee(2)
+            .build(&blockchain)
+            .unwrap();
+        blockchain.submit_transaction(first.clone()).unwrap();
+        let second = alice
+            .transaction(&address("Bob"), 20)
+            .valid_until(5)
+            .build(&blockchain)
+            .unwrap();
+
+        // Assert
+        assert_eq!(first.sender, alice.get_address());
+        assert_eq!((first.nonce, first.fee), (0, 2));
+        assert!(first.verify(&alice.public_key));
+        assert_eq!(
+            second.nonce, 1,
+            "The nonce should follow the pending transactions"
+        );
+        assert_eq!(second.fee, 0, "A mempool with room admits any fee");
+        assert_eq!(second.valid_until, Some(5));
+        assert_eq!(blockchain.submit_transaction(second), Ok(()));
+        blockchain.add_block(&miner()).unwrap();
+        assert_eq!(
+            alice
+                .transaction(&address("Bob"), 1)
+                .build(&blockchain)
+                .map(|transaction| transaction.nonce),
+            Ok(2),
+            "The nonce should follow the mined transactions"
+        );
+    }
+    #[test]
+    fn default_fee_outbids_a_full_mempool() {
+        // Arrange: A mempool holding one transaction paying a fee of 4
+        let mut config = mock_config();
+        config.blockchain.max_mempool_size = 1;
+        let mut blockchain = Blockchain::new(config).unwrap();
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+        blockchain.store.balances.insert(bob.get_address(), 100);
+        let pending = alice
+            .transaction(&address("Carol"), 10)
+            .fee(4)
+            .build(&blockchain)
+            .unwrap();
+        blockchain.submit_transaction(pending).unwrap();
+
+        // Act
+        let transaction = bob
+            .transaction(&address("Carol"), 10)
+            .build(&blockchain)
+            .unwrap();
+
+        // Assert
+        assert_eq!(transaction.fee, 5);
+        assert_eq!(blockchain.submit_transaction(transaction), Ok(()));
+    }
+    #[test]
+    fn transactions_that_cannot_be_submitted_are_not_built() {
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        let alice = Wallet::new();
+        blockchain.store.balances.insert(alice.get_address(), 100);
+
+        assert_eq!(
+            alice
+                .transaction(&address("Bob"), 100)
+                .fee(1)
+                .build(&blockchain),
+            Err(TransactionError::InsufficientBalance {
+                sender: alice.get_address(),
+                requested: 101,
+                available: 100
+            })
+        );
+        assert!(matches!(
+            alice.transaction("Bob", 10).build(&blockchain),
+            Err(TransactionError::InvalidAddress { .. })
+        ));
+        assert_eq!(
+            alice
+                .transaction(&address("Bob"), 10)
+                .nonce(1)
+                .build(&blockchain)
+                .map(|transaction| transaction.nonce),
+            Ok(1),
+            "Explicit nonces ahead of the next nonce should be allowed"
+        );
+    }
+}
@@ -1,10 +1,18 @@
+use crate::common::address::Network;
 use crate::common::compute_address_from_pub_key::compute_address_from_pub_key;
+use crate::core::transaction::Transaction;
+use crate::errors::keystore_errors::KeystoreError;
+use crate::wallet::keystore::{KdfParams, Keystore};
+use crate::wallet::transaction_builder::TransactionBuilder;
+use secp256k1::ecdsa::Signature;
 use secp256k1::{PublicKey, Secp256k1, SecretKey};
 use sha2::{Digest, Sha256};
+use std::path::Path;
 
 pub struct Wallet {
     pub private_key: SecretKey,
     pub public_key: PublicKey,
+    pub network: Network, // Network of the wallet address
 }
 
 impl Wallet {
@@ -14,19 +22,77 @@ impl Wallet {
         Wallet {
             private_key,
             public_key,
+            network: Network::default(),
         }
     }
+
+    /// Creates a wallet holding an existing secret key.
+    pub fn from_secret_key(private_key: SecretKey) -> Self {
+        let public_key = private_key.public_key(&Secp256k1::new());
+        Wallet {
+            private_key,
+            public_key,
+            network: Network::default(),
+        }
+    }
+
+    /// Moves the wallet to another network, changing its address but not its keys.
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Writes the private key to a keystore file encrypted with the password.
+    pub fn export_keystore<P: AsRef<Path>>(
+        &self,
+        path: P,
+        password: &str,
+    ) -> Result<(), KeystoreError> {
+        self.export_keystore_with(path, password, KdfParams::default())
+    }
+
+    /// Like `export_keystore`, with explicit key derivation costs.
+    pub fn export_keystore_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        password: &str,
+        params: KdfParams,
+    ) -> Result<(), KeystoreError> {
+        Keystore::encrypt(&self.private_key, self.network, password, params)?.save(path)
+    }
+
+    /// Restores a wallet from a keystore file written by `export_keystore`.
+    pub fn import_keystore<P: AsRef<Path>>(path: P, password: &str) -> Result<Self, KeystoreError> {
+        let keystore = Keystore::load(path)?;
+        let secret_key = keystore.decrypt(password)?;
+        Ok(Wallet::from_secret_key(secret_key).with_network(keystore.network()?))
+    }
+
     /// Uses the standalone function to get the wallet address.
     pub fn get_address(&self) -> String {
-        compute_address_from_pub_key(&self.public_key)
+        compute_address_from_pub_key(&self.public_key, self.network)
+    }
+
+    /// Signs the canonical payload of a transaction sent from this wallet and attaches the
+    /// signature and the public key to it.
+    pub fn sign(&self, transaction: &mut Transaction) {
+        let signature = self.sign_transaction(&transaction.signing_payload());
+        transaction.sign(signature, self.public_key);
+    }
+
+    /// Starts a transaction of `amount` from this wallet to the receiver; see
+    /// `TransactionBuilder` for the fields taken from the chain.
+    pub fn transaction(&self, receiver: &str, amount: u64) -> TransactionBuilder<'_> {
+        TransactionBuilder::new(self, receiver, amount)
     }
 
     /// Signs transaction data using the private key.
-    pub fn sign_transaction(&self, data: &str) -> String {
+    /// - `data` is the transaction's canonical signing payload.
+    pub fn sign_transaction(&self, data: &[u8]) -> Signature {
         let secp = Secp256k1::new();
-        let message_hash = Sha256::digest(data.as_bytes());
+        let message_hash = Sha256::digest(data);
         let message = secp256k1::Message::from_digest(message_hash.0);
-        secp.sign_ecdsa(&message, &self.private_key).to_string()
+        secp.sign_ecdsa(&message, &self.private_key)
     }
 }
 
@@ -46,4 +112,33 @@ mod tests {
         let wallet2 = Wallet::new();
         assert_ne!(wallet1.get_address(), wallet2.get_address());
     }
+    #[test]
+    fn wallet_survives_a_keystore_round_trip() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.yml");
+        let wallet = Wallet::new().with_network(Network::Testnet);
+        let params = KdfParams {
+            log_n: 4,
+            ..KdfParams::default()
+        };
+
+        // Act
+        wallet
+            .export_keystore_with(&path, "password", params)
+            .unwrap();
+        let restored = Wallet::import_keystore(&path, "password").unwrap();
+
+        // Assert
+        assert_eq!(restored.private_key, wallet.private_key);
+        assert_eq!(
+            restored.get_address(),
+            wallet.get_address(),
+            "The wallet should stay on its network"
+        );
+        assert!(matches!(
+            Wallet::import_keystore(&path, "wrong"),
+            Err(KeystoreError::WrongPassword)
+        ));
+    }
 }
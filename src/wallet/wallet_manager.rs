@@ -0,0 +1,310 @@
+use crate::common::address::Network;
+use crate::core::address_index::{AddressEntry, HistoryQuery};
+use crate::core::blockchain::Blockchain;
+use crate::core::transaction::Transaction;
+use crate::errors::wallet_errors::WalletError;
+use crate::storage::chain_store::ChainStore;
+use crate::wallet::wallet::Wallet;
+
+/// A wallet known by a label.
+pub struct Account {
+    pub label: String,
+    pub wallet: Wallet,
+}
+
+impl Account {
+    /// Returns the address of the account's wallet.
+    pub fn address(&self) -> String {
+        self.wallet.get_address()
+    }
+}
+
+/// Balance of an account, from the chain and from its pending transactions in the mempool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccountBalance {
+    pub confirmed: u64,        // Balance on the main chain
+    pub pending_sent: u64,     // Amounts plus fees of pending outgoing transactions
+    pub pending_received: u64, // Amounts of pending incoming transactions
+}
+
+impl AccountBalance {
+    /// Returns the confirmed balance not yet spent by pending transactions.
+    pub fn available(&self) -> u64 {
+        self.confirmed.saturating_sub(self.pending_sent)
+    }
+
+    /// Returns the balance once every pending transaction is confirmed.
+    pub fn unconfirmed(&self) -> u64 {
+        self.available().saturating_add(self.pending_received)
+    }
+
+    fn saturating_add(self, other: AccountBalance) -> Self {
+        AccountBalance {
+            confirmed: self.confirmed.saturating_add(other.confirmed),
+            pending_sent: self.pending_sent.saturating_add(other.pending_sent),
+            pending_received: self.pending_received.saturating_add(other.pending_received),
+        }
+    }
+}
+
+/// Labelled accounts whose balances and histories are read from a `Blockchain`.
+/// - Labels are unique and not empty, and every address belongs to a single account.
+/// - Accounts are kept in the order they were added.
+#[derive(Default)]
+pub struct WalletManager {
+    accounts: Vec<Account>,
+}
+
+impl WalletManager {
+    /// Creates a manager without accounts.
+    pub fn new() -> Self {
+        WalletManager::default()
+    }
+
+    /// Returns the number of accounts.
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Returns `true` if there is no account.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Returns the accounts in the order they were added.
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+
+    /// Returns the account with the given label.
+    pub fn account(&self, label: &str) -> Result<&Account, WalletError> {
+        self.accounts
+            .iter()
+            .find(|account| account.label == label)
+            .ok_or_else(|| WalletError::UnknownAccount {
+                label: label.to_string(),
+            })
+    }
+
+    /// Adds an existing wallet, for example one imported from a keystore, under a label.
+    pub fn add_account(&mut self, label: &str, wallet: Wallet) -> Result<&Account, WalletError> {
+        if label.is_empty() {
+            return Err(WalletError::EmptyLabel);
+        }
+        if self.account(label).is_ok() {
+            return Err(WalletError::DuplicateLabel {
+                label: label.to_string(),
+            });
+        }
+        let address = wallet.get_address();
+        if self
+            .accounts
+            .iter()
+            .any(|account| account.address() == address)
+        {
+            return Err(WalletError::DuplicateAddress { address });
+        }
+
+        self.accounts.push(Account {
+            label: label.to_string(),
+            wallet,
+        });
+        Ok(&self.accounts[self.accounts.len() - 1])
+    }
+
+    /// Adds an account with a freshly generated key on the network.
+    pub fn create_account(
+        &mut self,
+        label: &str,
+        network: Network,
+    ) -> Result<&Account, WalletError> {
+        self.add_account(label, Wallet::new().with_network(network))
+    }
+
+    /// Removes the account with the given label and returns it.
+    pub fn remove_account(&mut self, label: &str) -> Result<Account, WalletError> {
+        let position = self
+            .accounts
+            .iter()
+            .position(|account| account.label == label)
+            .ok_or_else(|| WalletError::UnknownAccount {
+                label: label.to_string(),
+            })?;
+        Ok(self.accounts.remove(position))
+    }
+
+    /// Returns the confirmed and pending balance of the account.
+    pub fn balance<S: ChainStore>(
+        &self,
+        label: &str,
+        blockchain: &Blockchain<S>,
+    ) -> Result<AccountBalance, WalletError> {
+        let address = self.account(label)?.address();
+        let mut balance = AccountBalance {
+            confirmed: blockchain.store.balance(&address).unwrap_or(0),
+            ..AccountBalance::default()
+        };
+        for pending in blockchain.mempool.transactions() {
+            if pending.sender == address {
+                balance.pending_sent = balance
+                    .pending_sent
+                    .saturating_add(pending.amount)
+                    .saturating_add(pending.fee);
+            }
+            if pending.receiver == address {
+                balance.pending_received = balance.pending_received.saturating_add(pending.amount);
+            }
+        }
+        Ok(balance)
+    }
+
+    /// Returns the sum of the balances of all accounts.
+    /// Pending transfers between two accounts count as both sent and received.
+    pub fn total_balance<S: ChainStore>(&self, blockchain: &Blockchain<S>) -> AccountBalance {
+        self.accounts
+            .iter()
+            .filter_map(|account| self.balance(&account.label, blockchain).ok())
+            .fold(AccountBalance::default(), AccountBalance::saturating_add)
+    }
+
+    /// Returns a page of the account's confirmed history, newest first (see `HistoryQuery`).
+    pub fn history<'a, S: ChainStore>(
+        &self,
+        label: &str,
+        blockchain: &'a Blockchain<S>,
+        query: &HistoryQuery,
+    ) -> Result<Vec<&'a AddressEntry>, WalletError> {
+        let address = self.account(label)?.address();
+        Ok(blockchain.get_address_history(&address, query))
+    }
+
+    /// Returns the account's outgoing transactions waiting in the mempool, in order of
+    /// arrival.
+    pub fn pending_transactions<'a, S: ChainStore>(
+        &self,
+        label: &str,
+        blockchain: &'a Blockchain<S>,
+    ) -> Result<Vec<&'a Transaction>, WalletError> {
+        let address = self.account(label)?.address();
+        Ok(blockchain
+            .mempool
+            .transactions()
+            .iter()
+            .filter(|pending| pending.sender == address)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::address_index::Direction;
+    use crate::test_utils::{miner, mock_config, signed_transaction};
+    #[test]
+    fn accounts_are_labelled_uniquely() {
+        let mut manager = WalletManager::new();
+        let address = manager
+            .create_account("savings", Network::Mainnet)
+            .unwrap()
+            .address();
+
+        assert_eq!(
+            manager
+                .create_account("savings", Network::Mainnet)
+                .map(|_| ()),
+            Err(WalletError::DuplicateLabel {
+                label: "savings".to_string()
+            })
+        );
+        assert_eq!(
+            manager.create_account("", Network::Mainnet).map(|_| ()),
+            Err(WalletError::EmptyLabel)
+        );
+        let same_key =
+            Wallet::from_secret_key(manager.account("savings").unwrap().wallet.private_key);
+        assert_eq!(
+            manager.add_account("copy", same_key).map(|_| ()),
+            Err(WalletError::DuplicateAddress { address }),
+            "An address should belong to a single account"
+        );
+
+        assert_eq!(manager.remove_account("savings").unwrap().label, "savings");
+        assert!(manager.is_empty());
+        assert_eq!(
+            manager.account("savings").map(|_| ()),
+            Err(WalletError::UnknownAccount {
+                label: "savings".to_string()
+            })
+        );
+    }
+    #[test]
+    fn balances_tell_confirmed_from_pending() {
+        // Arrange: Alice has 100 confirmed coins and sends 30 to Bob, both managed
+        let mut blockchain = Blockchain::new(mock_config()).unwrap();
+        let mut manager = WalletManager::new();
+        let alice = manager
+            .create_account("alice", Network::Mainnet)
+            .unwrap()
+            .address();
+        let bob = manager
+            .create_account("bob", Network::Mainnet)
+            .unwrap()
+            .address();
+        blockchain.store.balances.insert(alice.clone(), 100);
+        let transfer =
+            signed_transaction(&manager.account("alice").unwrap().wallet, &bob, 30, 2, 0);
+        blockchain.submit_transaction(transfer.clone()).unwrap();
+
+        // Act
+        let pending_alice = manager.balance("alice", &blockchain).unwrap();
+        let pending_bob = manager.balance("bob", &blockchain).unwrap();
+
+        // Assert: The transfer is pending
+        assert_eq!(
+            pending_alice,
+            AccountBalance {
+                confirmed: 100,
+                pending_sent: 32,
+                pending_received: 0
+            }
+        );
+        assert_eq!(pending_alice.available(), 68, "Fees should be reserved too");
+        assert_eq!(pending_bob.confirmed, 0);
+        assert_eq!(pending_bob.unconfirmed(), 30);
+        assert_eq!(
+            manager.pending_transactions("alice", &blockchain).unwrap(),
+            vec![&transfer]
+        );
+        assert_eq!(
+            manager.total_balance(&blockchain),
+            AccountBalance {
+                confirmed: 100,
+                pending_sent: 32,
+                pending_received: 30
+            }
+        );
+
+        // Act & Assert: Once mined, the transfer is confirmed
+        blockchain.add_block(&miner()).unwrap();
+        let confirmed_alice = manager.balance("alice", &blockchain).unwrap();
+        assert_eq!(confirmed_alice.confirmed, 68);
+        assert_eq!(confirmed_alice.unconfirmed(), 68);
+        assert_eq!(manager.balance("bob", &blockchain).unwrap().confirmed, 30);
+        assert!(manager
+            .pending_transactions("alice", &blockchain)
+            .unwrap()
+            .is_empty());
+        let history = manager
+            .history("bob", &blockchain, &HistoryQuery::default())
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, transfer.id());
+        assert_eq!(history[0].direction, Direction::Received);
+        assert_eq!(
+            manager.balance("carol", &blockchain),
+            Err(WalletError::UnknownAccount {
+                label: "carol".to_string()
+            })
+        );
+    }
+}